@@ -0,0 +1,70 @@
+use super::class::{HighlightClass, HighlightSpan};
+
+// 将高亮片段写为带ANSI转义序列的终端文本。
+// 跨行的片段逐行着色，使分页器按行显示时颜色不会错乱
+//
+// @param spans: 高亮片段
+//
+// @return: 终端文本
+pub fn to_ansi(spans: &[HighlightSpan]) -> String {
+    let mut out = String::new();
+
+    for span in spans {
+        let text = String::from_utf8_lossy(&span.text);
+        let color = match color(span.class) {
+            Some(color) => color,
+            None => {
+                out.push_str(&text);
+                continue;
+            }
+        };
+
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            if !line.is_empty() {
+                out.push_str("\x1b[");
+                out.push_str(color);
+                out.push('m');
+                out.push_str(line);
+                out.push_str("\x1b[0m");
+            }
+        }
+    }
+
+    out
+}
+
+// 获取类别对应的SGR参数，名字与空白不着色
+//
+// @param class: 高亮类别
+//
+// @return: SGR参数
+fn color(class: HighlightClass) -> Option<&'static str> {
+    match class {
+        HighlightClass::Keyword => Some("1;35"),
+        HighlightClass::Number => Some("33"),
+        HighlightClass::String | HighlightClass::LongString => Some("32"),
+        HighlightClass::Comment => Some("90"),
+        HighlightClass::Operator => Some("36"),
+        HighlightClass::Error => Some("4;31"),
+        HighlightClass::Name | HighlightClass::Whitespace => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlight::highlight;
+    use crate::lex::Dialect;
+
+    #[test]
+    fn highlight_ansi() {
+        let spans = highlight("x = 1 --[[a\nb]]", Dialect::Lua54).unwrap();
+        assert_eq!(
+            to_ansi(&spans),
+            "x \x1b[36m=\x1b[0m \x1b[33m1\x1b[0m \x1b[90m--[[a\x1b[0m\n\x1b[90mb]]\x1b[0m"
+        );
+    }
+}
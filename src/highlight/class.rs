@@ -0,0 +1,144 @@
+use crate::lex::{Dialect, LexToken, Span, TriviaKind};
+
+// 语法高亮的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HighlightClass {
+    Keyword,
+    Name,
+    Number,
+    String,
+    LongString,
+    Comment,
+    Operator,
+    Whitespace,
+    Error,
+}
+
+// 一段带有高亮类别的源码，text为其原始内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HighlightSpan {
+    pub class: HighlightClass,
+    pub text: Vec<u8>,
+    pub span: Span,
+}
+
+impl HighlightClass {
+    // 获取Token的高亮类别
+    //
+    // @param token: Token
+    // @param text: Token的原始内容，用于区分长字符串
+    // @param dialect: 语言方言，Luau中作为名字扫描的continue也按关键字高亮
+    //
+    // @return: 高亮类别
+    pub fn of_token(token: &LexToken<'_>, text: &[u8], dialect: Dialect) -> Self {
+        match token {
+            LexToken::Name(_) if dialect.has_continue() && text == b"continue" => {
+                HighlightClass::Keyword
+            }
+            LexToken::Name(_) => HighlightClass::Name,
+            LexToken::Number(_) => HighlightClass::Number,
+            LexToken::Str(_) | LexToken::ShortStr(_) if text.starts_with(b"[") => {
+                HighlightClass::LongString
+            }
+            LexToken::Str(_)
+            | LexToken::ShortStr(_)
+            | LexToken::InterpBegin(_)
+            | LexToken::InterpEnd(_)
+            | LexToken::InterpMid(_)
+            | LexToken::InterpSimple(_) => HighlightClass::String,
+            LexToken::Error(_) => HighlightClass::Error,
+            LexToken::Eof => HighlightClass::Whitespace,
+            _ => match token.text() {
+                Some(t) if t.as_bytes()[0].is_ascii_alphabetic() => HighlightClass::Keyword,
+                _ => HighlightClass::Operator,
+            },
+        }
+    }
+
+    // 获取空白或注释的高亮类别，shebang按注释高亮
+    //
+    // @param kind: 空白或注释的类型
+    //
+    // @return: 高亮类别
+    pub fn of_trivia(kind: TriviaKind) -> Self {
+        match kind {
+            TriviaKind::LineComment | TriviaKind::LongComment | TriviaKind::Shebang => {
+                HighlightClass::Comment
+            }
+            TriviaKind::Whitespace | TriviaKind::Newline | TriviaKind::Bom => {
+                HighlightClass::Whitespace
+            }
+        }
+    }
+
+    // 获取类别的名称，可用作HTML的class
+    //
+    // @return: 名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            HighlightClass::Keyword => "keyword",
+            HighlightClass::Name => "name",
+            HighlightClass::Number => "number",
+            HighlightClass::String => "string",
+            HighlightClass::LongString => "long-string",
+            HighlightClass::Comment => "comment",
+            HighlightClass::Operator => "operator",
+            HighlightClass::Whitespace => "whitespace",
+            HighlightClass::Error => "error",
+        }
+    }
+}
+
+impl HighlightSpan {
+    // 构造新的HighlightSpan
+    //
+    // @param class: 高亮类别
+    // @param text: 原始内容
+    // @param span: 在源码中的区间
+    //
+    // @return: HighlightSpan
+    pub fn new(class: HighlightClass, text: Vec<u8>, span: Span) -> Self {
+        HighlightSpan { class, text, span }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::{Interner, LexNumberValue};
+
+    #[test]
+    fn highlight_class_of_token() {
+        let mut interner = Interner::new();
+        let name = LexToken::Name(interner.intern("continue"));
+        let s = LexToken::ShortStr(interner.intern("x"));
+
+        let class = |token: &LexToken, text: &str| {
+            HighlightClass::of_token(token, text.as_bytes(), Dialect::Lua54)
+        };
+        assert_eq!(class(&LexToken::Local, "local"), HighlightClass::Keyword);
+        assert_eq!(class(&LexToken::Nil, "nil"), HighlightClass::Keyword);
+        assert_eq!(class(&LexToken::Dots, "..."), HighlightClass::Operator);
+        assert_eq!(class(&name, "continue"), HighlightClass::Name);
+        assert_eq!(
+            HighlightClass::of_token(&name, b"continue", Dialect::Luau),
+            HighlightClass::Keyword
+        );
+        assert_eq!(
+            class(&LexToken::Number(LexNumberValue::Int(1)), "1"),
+            HighlightClass::Number
+        );
+        assert_eq!(class(&s, "'x'"), HighlightClass::String);
+        assert_eq!(class(&s, "[[x]]"), HighlightClass::LongString);
+
+        assert_eq!(
+            HighlightClass::of_trivia(TriviaKind::Shebang),
+            HighlightClass::Comment
+        );
+        assert_eq!(
+            HighlightClass::of_trivia(TriviaKind::Newline),
+            HighlightClass::Whitespace
+        );
+        assert_eq!(HighlightClass::LongString.name(), "long-string");
+    }
+}
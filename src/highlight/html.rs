@@ -0,0 +1,65 @@
+use super::class::{HighlightClass, HighlightSpan};
+
+// 将高亮片段写为HTML，每个片段写为以类别名称为class的<span>，空白原样写出；
+// 结果不含外层标签，调用者可将其放入<pre>中以保留原有的格式
+//
+// @param spans: 高亮片段
+//
+// @return: HTML
+pub fn to_html(spans: &[HighlightSpan]) -> String {
+    let mut out = String::new();
+
+    for span in spans {
+        let text = String::from_utf8_lossy(&span.text);
+        if span.class == HighlightClass::Whitespace {
+            escape(&mut out, &text);
+            continue;
+        }
+
+        out.push_str("<span class=\"");
+        out.push_str(span.class.name());
+        out.push_str("\">");
+        escape(&mut out, &text);
+        out.push_str("</span>");
+    }
+
+    out
+}
+
+// 转义HTML中的特殊字符
+//
+// @param out: 输出
+// @param text: 原始内容
+fn escape(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            // BOM不应出现在页面中
+            '\u{feff}' => {}
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::highlight::highlight;
+    use crate::lex::Dialect;
+
+    #[test]
+    fn highlight_html() {
+        let spans = highlight("\u{feff}if a<b then\n  s = \"&\" end", Dialect::Lua54).unwrap();
+        assert_eq!(
+            to_html(&spans),
+            "<span class=\"keyword\">if</span> <span class=\"name\">a</span>\
+             <span class=\"operator\">&lt;</span><span class=\"name\">b</span> \
+             <span class=\"keyword\">then</span>\n  <span class=\"name\">s</span> \
+             <span class=\"operator\">=</span> <span class=\"string\">&quot;&amp;&quot;</span> \
+             <span class=\"keyword\">end</span>"
+        );
+    }
+}
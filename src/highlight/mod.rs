@@ -0,0 +1,9 @@
+pub mod ansi;
+pub mod class;
+pub mod html;
+pub mod scan;
+
+pub use ansi::to_ansi;
+pub use class::{HighlightClass, HighlightSpan};
+pub use html::to_html;
+pub use scan::highlight;
@@ -0,0 +1,103 @@
+use super::class::{HighlightClass, HighlightSpan};
+use crate::lex::{Dialect, LexError, LexStatus, LexToken, Trivia};
+
+// 将源码切分为带有高亮类别的片段，依次拼接各片段的内容即可还原出完整的源码。
+// 扫描以恢复模式进行，无法识别的部分作为Error片段保留
+//
+// @param src: Lua源码
+// @param dialect: 语言方言
+//
+// @return: 高亮片段
+pub fn highlight<S: AsRef<[u8]> + ?Sized>(
+    src: &S,
+    dialect: Dialect,
+) -> Result<Vec<HighlightSpan>, LexError> {
+    let mut lex = LexStatus::new(src);
+    lex.set_dialect(dialect);
+    lex.set_recovery(true);
+    lex.set_skip_header(true);
+    lex.setup();
+
+    let mut spans = Vec::new();
+    loop {
+        let token = lex.scan_lossless()?;
+        push_trivia(&mut spans, token.leading);
+
+        let eof = token.token.token == LexToken::Eof;
+        if !eof {
+            let class = HighlightClass::of_token(&token.token.token, &token.text, dialect);
+            spans.push(HighlightSpan::new(class, token.text, token.token.span));
+        }
+
+        push_trivia(&mut spans, token.trailing);
+        if eof {
+            return Ok(spans);
+        }
+    }
+}
+
+// 将空白及注释加入高亮片段
+//
+// @param spans: 高亮片段
+// @param trivia: 空白及注释
+fn push_trivia(spans: &mut Vec<HighlightSpan>, trivia: Vec<Trivia>) {
+    for t in trivia {
+        spans.push(HighlightSpan::new(
+            HighlightClass::of_trivia(t.kind),
+            t.text,
+            t.span,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classes(src: &str, dialect: Dialect) -> Vec<(HighlightClass, String)> {
+        highlight(src, dialect)
+            .unwrap()
+            .into_iter()
+            .filter(|s| s.class != HighlightClass::Whitespace)
+            .map(|s| (s.class, String::from_utf8(s.text).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn highlight_scan() {
+        use HighlightClass::*;
+
+        let src = "#!/bin/lua\nlocal s = [==[\nx]==] .. 'y' -- c\n--[[ d ]] return 0x10, $\n";
+        let spans = highlight(src, Dialect::Lua54).unwrap();
+        let text: Vec<u8> = spans.iter().flat_map(|s| s.text.clone()).collect();
+        assert_eq!(text, src.as_bytes());
+
+        let expect = [
+            (Comment, "#!/bin/lua"),
+            (Keyword, "local"),
+            (Name, "s"),
+            (Operator, "="),
+            (LongString, "[==[\nx]==]"),
+            (Operator, ".."),
+            (String, "'y'"),
+            (Comment, "-- c"),
+            (Comment, "--[[ d ]]"),
+            (Keyword, "return"),
+            (Number, "0x10"),
+            (Operator, ","),
+            (Error, "$"),
+        ];
+        let expect: Vec<_> = expect.iter().map(|(c, t)| (*c, t.to_string())).collect();
+        assert_eq!(classes(src, Dialect::Lua54), expect);
+
+        assert_eq!(
+            classes("continue `a{b}c`", Dialect::Luau),
+            [
+                (Keyword, "continue".to_string()),
+                (String, "`a{".to_string()),
+                (Name, "b".to_string()),
+                (String, "}c`".to_string()),
+            ]
+        );
+    }
+}
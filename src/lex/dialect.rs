@@ -0,0 +1,164 @@
+// 词法分析所遵循的语言方言，决定可用的关键字、运算符、转义序列及数字字面量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    Lua51,
+    Lua52,
+    Lua53,
+    #[default]
+    Lua54,
+    LuaJit,
+    Luau,
+}
+
+impl Dialect {
+    // 是否支持goto语句及::label::
+    //
+    // @return: 是否支持
+    pub fn has_goto(&self) -> bool {
+        !matches!(self, Dialect::Lua51 | Dialect::Luau)
+    }
+
+    // 是否区分整数与浮点数，不区分时所有数字字面量均为浮点数
+    //
+    // @return: 是否支持
+    pub fn has_integers(&self) -> bool {
+        matches!(self, Dialect::Lua53 | Dialect::Lua54)
+    }
+
+    // 是否支持位运算符(&、|、~、<<、>>)
+    //
+    // @return: 是否支持
+    pub fn has_bitwise_operators(&self) -> bool {
+        matches!(self, Dialect::Lua53 | Dialect::Lua54)
+    }
+
+    // 是否支持整除运算符(//)
+    //
+    // @return: 是否支持
+    pub fn has_floor_division(&self) -> bool {
+        matches!(self, Dialect::Lua53 | Dialect::Lua54 | Dialect::Luau)
+    }
+
+    // 是否支持\x及\z转义序列
+    //
+    // @return: 是否支持
+    pub fn has_extended_escapes(&self) -> bool {
+        !matches!(self, Dialect::Lua51)
+    }
+
+    // 是否支持\u{XXX}转义序列
+    //
+    // @return: 是否支持
+    pub fn has_utf8_escapes(&self) -> bool {
+        !matches!(self, Dialect::Lua51 | Dialect::Lua52)
+    }
+
+    // \u{XXX}转义序列所允许的最大码点
+    //
+    // @return: 最大码点
+    pub fn max_utf8_escape(&self) -> u32 {
+        match self {
+            Dialect::Lua54 => 0x7fffffff,
+            _ => 0x10ffff,
+        }
+    }
+
+    // 是否将无法识别的转义序列视为错误；Lua 5.1中\q等转义序列直接得到字符本身
+    //
+    // @return: 是否视为错误
+    pub fn has_strict_escapes(&self) -> bool {
+        !matches!(self, Dialect::Lua51)
+    }
+
+    // 是否支持十六进制浮点数(小数部分及p指数)
+    //
+    // @return: 是否支持
+    pub fn has_hex_floats(&self) -> bool {
+        !matches!(self, Dialect::Lua51 | Dialect::Luau)
+    }
+
+    // 是否支持以0b开头的二进制整数
+    //
+    // @return: 是否支持
+    pub fn has_binary_literals(&self) -> bool {
+        matches!(self, Dialect::LuaJit | Dialect::Luau)
+    }
+
+    // 是否支持Luau的复合赋值运算符(+=、-=、..=等)
+    //
+    // @return: 是否支持
+    pub fn has_compound_assignment(&self) -> bool {
+        matches!(self, Dialect::Luau)
+    }
+
+    // 是否支持Luau以`包围的插值字符串
+    //
+    // @return: 是否支持
+    pub fn has_interpolated_strings(&self) -> bool {
+        matches!(self, Dialect::Luau)
+    }
+
+    // 是否将continue视为跳过本次循环的语句；continue仍被扫描为Name，由语法分析根据上下文判断
+    //
+    // @return: 是否支持
+    pub fn has_continue(&self) -> bool {
+        matches!(self, Dialect::Luau)
+    }
+
+    // 是否支持Luau的if-then-else表达式
+    //
+    // @return: 是否支持
+    pub fn has_if_expressions(&self) -> bool {
+        matches!(self, Dialect::Luau)
+    }
+
+    // 是否支持Lua 5.4的局部变量属性(<const>、<close>)
+    //
+    // @return: 是否支持
+    pub fn has_local_attributes(&self) -> bool {
+        matches!(self, Dialect::Lua54)
+    }
+
+    // 是否支持LuaJIT的64位整数及虚数字面量后缀(LL、ULL、i)
+    //
+    // @return: 是否支持
+    pub fn has_cdata_literals(&self) -> bool {
+        matches!(self, Dialect::LuaJit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dialect_features() {
+        assert_eq!(Dialect::default(), Dialect::Lua54);
+
+        assert!(!Dialect::Lua51.has_goto());
+        assert!(Dialect::Lua52.has_goto());
+        assert!(Dialect::LuaJit.has_goto());
+
+        assert!(!Dialect::Lua52.has_bitwise_operators());
+        assert!(Dialect::Lua53.has_bitwise_operators());
+        assert!(!Dialect::LuaJit.has_floor_division());
+
+        assert!(!Dialect::Lua52.has_utf8_escapes());
+        assert_eq!(Dialect::Lua53.max_utf8_escape(), 0x10ffff);
+        assert_eq!(Dialect::Lua54.max_utf8_escape(), 0x7fffffff);
+
+        assert!(!Dialect::Lua54.has_binary_literals());
+        assert!(Dialect::LuaJit.has_binary_literals());
+        assert!(!Dialect::LuaJit.has_integers());
+
+        assert!(!Dialect::Luau.has_goto());
+        assert!(!Dialect::Luau.has_bitwise_operators());
+        assert!(Dialect::Luau.has_floor_division());
+        assert!(Dialect::Luau.has_compound_assignment());
+        assert!(!Dialect::Lua54.has_interpolated_strings());
+        assert!(Dialect::Luau.has_continue());
+        assert!(Dialect::Luau.has_if_expressions());
+        assert!(!Dialect::Lua53.has_local_attributes());
+        assert!(Dialect::Lua54.has_local_attributes());
+    }
+}
@@ -0,0 +1,138 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+// 转义序列错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexEscapeError {
+    Invalid,
+    HexDigitExpected,
+    MissingLeftBrace,
+    MissingRightBrace,
+    Utf8TooLarge,
+    DecimalTooLarge,
+}
+
+// 词法错误类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LexErrorKind {
+    UnfinishedString,
+    UnfinishedLongString(u32),
+    UnfinishedLongComment(u32),
+    InvalidLongStringDelimiter,
+    MalformedNumber,
+    InvalidEscape(LexEscapeError),
+    UnexpectedSymbol,
+    DoubleBrace,
+    ReadFailed(io::ErrorKind),
+}
+
+// 词法错误，包含出错位置及出错处附近的源码
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LexError {
+    pub kind: LexErrorKind,
+    pub line: u32,
+    pub column: u32,
+    pub near: String,
+}
+
+impl LexError {
+    // 构造新的LexError
+    //
+    // @param kind: 错误类型
+    // @param line: 出错的行号
+    // @param column: 出错的列号
+    // @param near: 出错处附近的源码
+    //
+    // @return: LexError
+    pub fn new(kind: LexErrorKind, line: u32, column: u32, near: String) -> Self {
+        LexError {
+            kind,
+            line,
+            column,
+            near,
+        }
+    }
+}
+
+impl fmt::Display for LexEscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LexEscapeError::Invalid => "invalid escape sequence",
+            LexEscapeError::HexDigitExpected => "hexadecimal digit expected",
+            LexEscapeError::MissingLeftBrace => "missing '{' in \\u{xxxx}",
+            LexEscapeError::MissingRightBrace => "missing '}' in \\u{xxxx}",
+            LexEscapeError::Utf8TooLarge => "UTF-8 value too large",
+            LexEscapeError::DecimalTooLarge => "decimal escape too large",
+        })
+    }
+}
+
+impl fmt::Display for LexErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexErrorKind::UnfinishedString => f.write_str("unfinished string"),
+            LexErrorKind::UnfinishedLongString(line) => {
+                write!(f, "unfinished long string (starting at line {})", line)
+            }
+            LexErrorKind::UnfinishedLongComment(line) => {
+                write!(f, "unfinished long comment (starting at line {})", line)
+            }
+            LexErrorKind::InvalidLongStringDelimiter => {
+                f.write_str("invalid long string delimiter")
+            }
+            LexErrorKind::MalformedNumber => f.write_str("malformed number"),
+            LexErrorKind::InvalidEscape(e) => e.fmt(f),
+            LexErrorKind::UnexpectedSymbol => f.write_str("unexpected symbol"),
+            LexErrorKind::DoubleBrace => {
+                f.write_str("double braces are not permitted within interpolated strings")
+            }
+            LexErrorKind::ReadFailed(e) => write!(f, "cannot read source ({})", e),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} near '{}'",
+            self.line, self.column, self.kind, self.near
+        )
+    }
+}
+
+impl Error for LexError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lex_error_display() {
+        let e = LexError::new(LexErrorKind::UnfinishedString, 3, 7, String::from("\"abc"));
+        assert_eq!(e.to_string(), "3:7: unfinished string near '\"abc'");
+
+        let e = LexError::new(
+            LexErrorKind::UnfinishedLongComment(1),
+            4,
+            1,
+            String::from("<eof>"),
+        );
+        assert_eq!(
+            e.to_string(),
+            "4:1: unfinished long comment (starting at line 1) near '<eof>'"
+        );
+
+        let e = LexError::new(
+            LexErrorKind::InvalidEscape(LexEscapeError::HexDigitExpected),
+            1,
+            5,
+            String::from("'\\xg"),
+        );
+        assert_eq!(
+            e.to_string(),
+            "1:5: hexadecimal digit expected near ''\\xg'"
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use super::dialect::Dialect;
+use super::error::LexError;
+use super::span::{Position, SpannedToken};
+use super::state::LexStatus;
+use super::symbol::Interner;
+use super::token::LexToken;
+use std::mem;
+use std::ops::Range;
+
+// 一次文本编辑：将旧源码中range区间内的字节替换为text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit<'a> {
+    pub range: Range<usize>,
+    pub text: &'a [u8],
+}
+
+impl<'a> TextEdit<'a> {
+    // 构造新的TextEdit
+    //
+    // @param range: 被替换的区间，以旧源码的字节偏移计
+    // @param text: 替换后的内容
+    //
+    // @return: TextEdit
+    pub fn new<S: AsRef<[u8]> + ?Sized>(range: Range<usize>, text: &'a S) -> Self {
+        TextEdit {
+            range,
+            text: text.as_ref(),
+        }
+    }
+
+    // 将编辑应用到旧源码上
+    //
+    // @param src: 旧源码
+    //
+    // @return: 新源码
+    pub fn apply(&self, src: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(src.len() + self.text.len() - self.range.len());
+        out.extend_from_slice(&src[..self.range.start]);
+        out.extend_from_slice(self.text);
+        out.extend_from_slice(&src[self.range.end..]);
+        out
+    }
+}
+
+// 完整扫描一段源码，得到以Eof结尾、不再借用源码的Token序列，作为增量扫描的起点
+//
+// @param src: Lua源码
+// @param dialect: 语言方言
+// @param interner: 字符串驻留表，之后的增量扫描需要继续使用同一个驻留表
+//
+// @return: Token序列
+pub fn lex_tokens(
+    src: &[u8],
+    dialect: Dialect,
+    interner: &mut Interner,
+) -> Result<Vec<SpannedToken<'static>>, LexError> {
+    let mut lex = LexStatus::new(src);
+    lex.set_dialect(dialect);
+    lex.set_interner(mem::take(interner));
+    lex.set_skip_header(true);
+    lex.setup();
+
+    let tokens = scan_until(&mut lex, |_, _| None);
+    *interner = lex.into_interner();
+
+    tokens.map(|(tokens, _)| tokens)
+}
+
+// 根据一次文本编辑增量地重新扫描，只扫描受影响的区间，其余Token沿用旧序列并平移其区间。
+// 重新扫描从编辑位置之前最后一个完整的、不在插值字符串内的Token之后开始，
+// 直到新Token的起点越过编辑区间且与某个旧Token的起点重合为止：此后两次扫描面对的是相同的剩余源码，
+// 得到的Token必然相同。编辑打开或关闭长字符串、长注释，或改变其中的'='个数时，
+// 新旧Token的起点会一直错开，扫描会继续到长括号重新闭合处或源码末尾
+//
+// @param tokens: 旧源码的完整Token序列，以Eof结尾；成功时更新为新源码的Token序列，失败时保持不变
+// @param src: 编辑后的完整源码
+// @param edit: 文本编辑
+// @param dialect: 语言方言，需与扫描旧Token时一致
+// @param interner: 扫描旧Token时使用的字符串驻留表
+//
+// @return: 新序列中被重新扫描的Token的下标区间
+pub fn relex(
+    tokens: &mut Vec<SpannedToken<'static>>,
+    src: &[u8],
+    edit: &TextEdit,
+    dialect: Dialect,
+    interner: &mut Interner,
+) -> Result<Range<usize>, LexError> {
+    let depths = interp_depths(tokens);
+
+    // 重新扫描的起点：编辑位置之前的Token的结束位置，扫描该Token时向后查看的一个字符也必须在编辑之前
+    let keep = (0..tokens.len())
+        .rev()
+        .find(|&i| tokens[i].span.end.offset < edit.range.start && depths[i + 1] == 0)
+        .map_or(0, |i| i + 1);
+
+    let mut lex = if keep == 0 {
+        let mut lex = LexStatus::new(src);
+        lex.set_dialect(dialect);
+        lex.set_interner(mem::take(interner));
+        lex.set_skip_header(true);
+        lex.setup();
+        lex
+    } else {
+        let start = tokens[keep - 1].span.end;
+        let mut lex = LexStatus::new(&src[start.offset..]);
+        lex.set_dialect(dialect);
+        lex.set_interner(mem::take(interner));
+        lex.setup_at(start);
+        lex
+    };
+
+    let edit_end = edit.range.start + edit.text.len();
+    let old_tokens = &tokens[keep..];
+    let old_depths = &depths[keep..];
+    let result = scan_until(&mut lex, |token, depth| {
+        let start = token.span.start.offset;
+        if start < edit_end || depth != 0 {
+            return None;
+        }
+
+        let old_start = start + edit.range.len() - edit.text.len();
+        old_tokens
+            .binary_search_by_key(&old_start, |t| t.span.start.offset)
+            .ok()
+            .filter(|&j| old_depths[j] == 0)
+    });
+    *interner = lex.into_interner();
+
+    let (new_tokens, sync) = result?;
+    let count = new_tokens.len();
+    let tail = match sync {
+        Some((j, position)) => {
+            let shift = Shift::new(old_tokens[j].span.start, position);
+            tokens.drain(keep..keep + j);
+            tokens[keep..]
+                .iter_mut()
+                .for_each(|t| shift.apply(&mut t.span.start, &mut t.span.end));
+            tokens.split_off(keep)
+        }
+        None => Vec::new(),
+    };
+
+    tokens.truncate(keep);
+    tokens.extend(new_tokens);
+    tokens.extend(tail);
+
+    Ok(keep..keep + count)
+}
+
+// 持续扫描Token直到Eof，或直到sync为新扫描到的Token找到了可以沿用的旧Token
+//
+// @param lex: 词法分析器
+// @param sync: 判断能否从该Token起沿用旧Token，参数为Token及扫描该Token前的插值字符串层数
+//
+// @return: 新扫描的Token，以及可沿用的旧Token下标与新Token的起点
+#[allow(clippy::type_complexity)]
+fn scan_until<F>(
+    lex: &mut LexStatus<'_>,
+    mut sync: F,
+) -> Result<(Vec<SpannedToken<'static>>, Option<(usize, Position)>), LexError>
+where
+    F: FnMut(&SpannedToken<'_>, usize) -> Option<usize>,
+{
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+
+    loop {
+        let token = lex.scan_spanned()?;
+        if let Some(j) = sync(&token, depth) {
+            return Ok((tokens, Some((j, token.span.start))));
+        }
+
+        depth = next_depth(depth, &token.token);
+        let eof = token.token == LexToken::Eof;
+        tokens.push(token.into_owned());
+        if eof {
+            return Ok((tokens, None));
+        }
+    }
+}
+
+// 计算每个Token之前尚未结束的插值字符串层数，下标i为第i个Token之前的层数，最后一项为全部Token之后的层数
+//
+// @param tokens: Token序列
+//
+// @return: 各位置的层数
+fn interp_depths(tokens: &[SpannedToken<'_>]) -> Vec<usize> {
+    let mut depths = Vec::with_capacity(tokens.len() + 1);
+    let mut depth = 0;
+
+    depths.push(depth);
+    for token in tokens {
+        depth = next_depth(depth, &token.token);
+        depths.push(depth);
+    }
+
+    depths
+}
+
+// 计算Token之后尚未结束的插值字符串层数
+//
+// @param depth: Token之前的层数
+// @param token: Token
+//
+// @return: Token之后的层数
+fn next_depth(depth: usize, token: &LexToken<'_>) -> usize {
+    match token {
+        LexToken::InterpBegin(_) => depth + 1,
+        LexToken::InterpEnd(_) => depth.saturating_sub(1),
+        _ => depth,
+    }
+}
+
+// 沿用的旧Token需要平移的位置：偏移与行号整体平移，列号只在同步点所在的行上平移
+struct Shift {
+    old: Position,
+    new: Position,
+}
+
+impl Shift {
+    // 构造新的Shift
+    //
+    // @param old: 同步点在旧源码中的位置
+    // @param new: 同步点在新源码中的位置
+    //
+    // @return: Shift
+    fn new(old: Position, new: Position) -> Self {
+        Shift { old, new }
+    }
+
+    // 平移一个Token的区间
+    //
+    // @param start: 起始位置
+    // @param end: 结束位置
+    fn apply(&self, start: &mut Position, end: &mut Position) {
+        for pos in [start, end] {
+            if pos.line == self.old.line {
+                pos.column = pos.column + self.new.column - self.old.column;
+            }
+            pos.line = pos.line + self.new.line - self.old.line;
+            pos.offset = pos.offset + self.new.offset - self.old.offset;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::error::LexErrorKind;
+    use crate::toolbox::prng::{Prng, PrngState};
+
+    // 将Token序列写成便于比较的形式，名字与字符串按源码形式写出
+    fn render(tokens: &[SpannedToken<'_>], interner: &Interner) -> Vec<String> {
+        tokens
+            .iter()
+            .map(|t| {
+                format!(
+                    "{} {}:{}:{}-{}:{}:{}",
+                    t.token.source(interner),
+                    t.span.start.offset,
+                    t.span.start.line,
+                    t.span.start.column,
+                    t.span.end.offset,
+                    t.span.end.line,
+                    t.span.end.column
+                )
+            })
+            .collect()
+    }
+
+    // 对src应用一次编辑，比较增量扫描与完整扫描的结果，并返回被重新扫描的区间
+    fn check_edit(dialect: Dialect, src: &str, range: Range<usize>, text: &str) -> Range<usize> {
+        let mut interner = Interner::new();
+        let mut tokens = lex_tokens(src.as_bytes(), dialect, &mut interner).unwrap();
+
+        let edit = TextEdit::new(range, text);
+        let new_src = edit.apply(src.as_bytes());
+        let changed = relex(&mut tokens, &new_src, &edit, dialect, &mut interner).unwrap();
+
+        let full = lex_tokens(&new_src, dialect, &mut interner).unwrap();
+        assert_eq!(
+            render(&tokens, &interner),
+            render(&full, &interner),
+            "{:?}",
+            String::from_utf8_lossy(&new_src)
+        );
+
+        changed
+    }
+
+    #[test]
+    fn relex_local_edit() {
+        let src = "local a = 1\nlocal bc = a + 2\nprint(bc)\n";
+
+        // 修改名字只需重新扫描该名字
+        assert_eq!(check_edit(Dialect::Lua54, src, 18..20, "xyz"), 5..6);
+        // 在名字末尾追加字符会与原来的名字合并
+        assert_eq!(check_edit(Dialect::Lua54, src, 20..20, "d"), 5..6);
+        // 插入换行后同一行后面的Token需要平移列号，之后的行只平移行号
+        check_edit(Dialect::Lua54, src, 23..23, "\n\n");
+        check_edit(Dialect::Lua54, src, 11..12, " ");
+        check_edit(Dialect::Lua54, src, 0..0, "#!/usr/bin/lua\n");
+        check_edit(Dialect::Lua54, src, 0..5, "");
+        check_edit(Dialect::Lua54, src, src.len()..src.len(), "x");
+    }
+
+    #[test]
+    fn relex_long_brackets() {
+        let src = "a = 1\nb = [==[ x ]] ]=] ]==] ]===] c = 2\n-- d\ne = f\n--]==]\ng = 1\n";
+
+        // 改变'='的个数使长字符串在不同的位置结束
+        check_edit(Dialect::Lua54, src, 11..12, "");
+        check_edit(Dialect::Lua54, src, 11..11, "=");
+        check_edit(Dialect::Lua54, src, 20..21, "");
+
+        // 打开长注释后，直到]]为止的Token都会被吞掉，被长字符串吞掉的]=]等则重新出现
+        let changed = check_edit(Dialect::Lua54, src, 0..0, "--[[");
+        assert_eq!(changed.start, 0);
+        check_edit(Dialect::Lua54, src, 43..43, "[==[");
+
+        // 关闭长注释后被吞掉的Token重新出现
+        let src = "--[=[ a = 1\nb = 2 ]=]\nc = 3 ]==]\n";
+        check_edit(Dialect::Lua54, src, 2..5, "");
+        check_edit(Dialect::Lua54, src, 3..4, "==");
+    }
+
+    #[test]
+    fn relex_luau_interpolation() {
+        let src = "local s = `a{b}c{ {1}[1]}d`\nx += 1\n";
+
+        check_edit(Dialect::Luau, src, 13..14, "bb");
+        check_edit(Dialect::Luau, src, 19..20, "2");
+        check_edit(Dialect::Luau, src, 15..16, "");
+        check_edit(Dialect::Luau, src, 25..26, "");
+        check_edit(Dialect::Luau, src, 28..28, "y");
+    }
+
+    #[test]
+    fn relex_random_edits() {
+        const PIECES: [&str; 12] = [
+            "[[", "]]", "[=[", "]=]", "=", "--", "\n", " ", "a", "1", "'", "\"",
+        ];
+        let mut prng = PrngState::new();
+        prng.seed_secure();
+
+        let mut src = b"local t = { [[x]], [=[ y ]] ]=] } -- c\nprint(t[1] .. 'z')\n".to_vec();
+        let mut interner = Interner::new();
+        let mut tokens = lex_tokens(&src, Dialect::Lua54, &mut interner).unwrap();
+        let mut applied = 0;
+
+        for _ in 0..500 {
+            let start = prng.gen_u64() as usize % (src.len() + 1);
+            let end = start + prng.gen_u64() as usize % 3;
+            let end = end.min(src.len());
+            let text = PIECES[prng.gen_u64() as usize % PIECES.len()];
+
+            let edit = TextEdit::new(start..end, text);
+            let new_src = edit.apply(&src);
+            let full = match lex_tokens(&new_src, Dialect::Lua54, &mut interner) {
+                Ok(full) => full,
+                Err(_) => continue,
+            };
+
+            relex(&mut tokens, &new_src, &edit, Dialect::Lua54, &mut interner).unwrap();
+            assert_eq!(render(&tokens, &interner), render(&full, &interner));
+            src = new_src;
+            applied += 1;
+        }
+        assert!(applied > 100, "{}", applied);
+    }
+
+    #[test]
+    fn relex_error() {
+        let mut interner = Interner::new();
+        let src = b"a = 'x'\nb = 2\n";
+        let mut tokens = lex_tokens(src, Dialect::Lua54, &mut interner).unwrap();
+        let before = render(&tokens, &interner);
+
+        let edit = TextEdit::new(6..7, "");
+        let new_src = edit.apply(src);
+        let err = relex(&mut tokens, &new_src, &edit, Dialect::Lua54, &mut interner).unwrap_err();
+        assert_eq!(err.kind, LexErrorKind::UnfinishedString);
+        assert_eq!(render(&tokens, &interner), before);
+    }
+}
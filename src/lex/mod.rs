@@ -1,5 +1,22 @@
+pub mod dialect;
+pub mod error;
+pub mod incremental;
 mod number;
+pub mod reader;
+pub mod span;
 pub mod state;
+pub mod stream;
+pub mod symbol;
 pub mod token;
+pub mod trivia;
 
-pub use token::{LexNumberValue, LexToken};
+pub use dialect::Dialect;
+pub use error::{LexError, LexErrorKind, LexEscapeError};
+pub use incremental::{lex_tokens, relex, TextEdit};
+pub use reader::{BufReadReader, ChunkReader, IoReader, LexReader};
+pub use span::{Position, Span, SpannedToken};
+pub use state::LexStatus;
+pub use stream::TokenStream;
+pub use symbol::{Interner, Symbol, MAX_SHORT_LEN};
+pub use token::{LexNumberValue, LexToken, LexTokenSource};
+pub use trivia::{LosslessToken, Trivia, TriviaKind};
@@ -1,122 +1,413 @@
+use super::dialect::Dialect;
+
+// 数字字面量的值，整数为64位有符号整数；
+// Int64、UInt64与Imaginary为LuaJIT中带LL、ULL与i后缀的字面量
+#[derive(Debug, Clone, Copy)]
 pub enum LexNumberValue {
     Invalid,
-    UInt(u64),
+    Int(i64),
     Float(f64),
+    Int64(i64),
+    UInt64(u64),
+    Imaginary(f64),
+}
+
+// 判断数字是否以"0x"或"0b"一类的前缀开头，前缀字母不区分大小写
+//
+// @param v: 数字的源码
+// @param c: 前缀字母的小写形式
+//
+// @return: 是否以该前缀开头
+fn has_prefix(v: &[u8], c: u8) -> bool {
+    v.len() >= 2 && v[0] == b'0' && (v[1] | 0x20) == c
+}
+
+// 获取十六进制数字的值
+//
+// @param c: 字符
+//
+// @return: 数字的值
+fn hex_digit(c: u8) -> Option<u64> {
+    char::from(c).to_digit(16).map(u64::from)
+}
+
+// 将m >> shift按照就近舍入(向偶数舍入)的规则取整
+//
+// @param m: 尾数
+// @param shift: 右移的位数，可以超过64
+// @param sticky: 尾数之后是否还有被舍弃的非零位
+//
+// @return: 取整后的尾数
+fn round_shift(m: u64, shift: u32, sticky: bool) -> u64 {
+    if shift == 0 {
+        return m;
+    }
+    if shift > 64 {
+        return 0;
+    }
+
+    let r = if shift == 64 { 0 } else { m >> shift };
+    let mask = if shift == 64 {
+        u64::MAX
+    } else {
+        (1u64 << shift) - 1
+    };
+    let low = m & mask;
+    let half = 1u64 << (shift - 1);
+
+    if low > half || (low == half && (sticky || r & 1 == 1)) {
+        r + 1
+    } else {
+        r
+    }
+}
+
+// 将m * 2^e转换为最接近的f64
+//
+// @param m: 尾数
+// @param e: 二进制指数
+// @param sticky: 尾数之后是否还有被舍弃的非零位
+//
+// @return: 转换得到的浮点数
+fn ldexp_rounded(m: u64, e: i64, sticky: bool) -> f64 {
+    if m == 0 {
+        return 0f64;
+    }
+
+    // 规格化，使尾数的最高位为1，此时数值为1.xxx * 2^exp
+    let lz = m.leading_zeros();
+    let m = m << lz;
+    let exp = e - i64::from(lz) + 63;
+
+    if exp > 1023 {
+        return f64::INFINITY;
+    }
+
+    if exp >= -1022 {
+        let mut r = round_shift(m, 11, sticky);
+        let mut exp = exp;
+        if r == 1u64 << 53 {
+            r >>= 1;
+            exp += 1;
+        }
+        if exp > 1023 {
+            return f64::INFINITY;
+        }
+
+        f64::from_bits((((exp + 1023) as u64) << 52) | (r & ((1u64 << 52) - 1)))
+    } else {
+        // 非规格化数，可保留的尾数位数随指数减小而减少；
+        // 舍入进位到2^52时恰好得到最小的规格化数
+        let keep = exp + 1074;
+        if keep < -1 {
+            return 0f64;
+        }
+
+        f64::from_bits(round_shift(m, (63 - keep) as u32, sticky))
+    }
+}
+
+// 将不带小数部分及指数的整数字面量解析为u64，超出范围时返回None
+//
+// @param v: 数字的源码
+//
+// @return: 数字的值
+fn parse_u64(v: &[u8]) -> Option<u64> {
+    let (digits, radix) = if has_prefix(v, b'x') {
+        (&v[2..], 16)
+    } else if has_prefix(v, b'b') {
+        (&v[2..], 2)
+    } else {
+        (v, 10)
+    };
+
+    if digits.is_empty() {
+        return None;
+    }
+
+    digits.iter().try_fold(0u64, |value, c| {
+        let d = char::from(*c).to_digit(radix)?;
+        value
+            .checked_mul(u64::from(radix))?
+            .checked_add(u64::from(d))
+    })
 }
 
 impl LexNumberValue {
-    pub fn new(v: &Vec<char>) -> Self {
-        if v.starts_with(&['0', 'b']) {
+    // 按照方言解析数字字面量，方言不支持的字面量形式视为无效；
+    // 不区分整数的方言中，整数字面量按浮点数解析
+    //
+    // @param v: 数字的源码
+    // @param dialect: 语言方言
+    //
+    // @return: 数字的值
+    pub fn new_dialect(v: &[u8], dialect: Dialect) -> Self {
+        if has_prefix(v, b'b') && !dialect.has_binary_literals() {
+            return LexNumberValue::Invalid;
+        }
+        if has_prefix(v, b'x')
+            && !dialect.has_hex_floats()
+            && v.iter().any(|c| *c == b'.' || (*c | 0x20) == b'p')
+        {
+            return LexNumberValue::Invalid;
+        }
+
+        let value = if dialect.has_cdata_literals() {
+            LexNumberValue::new_luajit(v)
+        } else {
+            LexNumberValue::new(v)
+        };
+        if dialect.has_integers() {
+            return value;
+        }
+
+        match value {
+            // 十六进制整数不再按2^64取模，而是与其他浮点数一样舍入
+            LexNumberValue::Int(_) if has_prefix(v, b'x') => {
+                LexNumberValue::new_hex(&[v, b"p0"].concat())
+            }
+            LexNumberValue::Int(value) => LexNumberValue::Float(value as u64 as f64),
+            _ => value,
+        }
+    }
+
+    pub fn new(v: &[u8]) -> Self {
+        if has_prefix(v, b'b') {
             LexNumberValue::new_binary(v)
-        } else if v.starts_with(&['0', 'x']) {
+        } else if has_prefix(v, b'x') {
             LexNumberValue::new_hex(v)
         } else {
             LexNumberValue::new_dec(v)
         }
     }
 
-    pub fn new_binary(v: &Vec<char>) -> Self {
-        if !v.starts_with(&['0', 'b']) {
+    // 解析LuaJIT的数字字面量，支持LL、ULL及i后缀，后缀不区分大小写
+    //
+    // @param v: 数字的源码
+    //
+    // @return: 数字的值
+    pub fn new_luajit(v: &[u8]) -> Self {
+        let body = |suffix: &[u8]| {
+            let n = v.len().checked_sub(suffix.len())?;
+            if v[n..].iter().zip(suffix).all(|(c, s)| (c | 0x20) == *s) {
+                Some(&v[..n])
+            } else {
+                None
+            }
+        };
+
+        if let Some(body) = body(b"ull") {
+            match parse_u64(body) {
+                Some(value) => LexNumberValue::UInt64(value),
+                None => LexNumberValue::Invalid,
+            }
+        } else if let Some(body) = body(b"ll") {
+            match parse_u64(body) {
+                Some(value) => LexNumberValue::Int64(value as i64),
+                None => LexNumberValue::Invalid,
+            }
+        } else if let Some(body) = body(b"i") {
+            match LexNumberValue::new(body) {
+                LexNumberValue::Int(value) => LexNumberValue::Imaginary(value as f64),
+                LexNumberValue::Float(value) => LexNumberValue::Imaginary(value),
+                _ => LexNumberValue::Invalid,
+            }
+        } else {
+            LexNumberValue::new(v)
+        }
+    }
+
+    pub fn new_binary(v: &[u8]) -> Self {
+        if !has_prefix(v, b'b') {
             return LexNumberValue::Invalid;
         }
 
+        if v.len() == 2 {
+            return LexNumberValue::Invalid;
+        }
+
+        // 与十六进制整数一样，溢出时按2^64取模
         let mut value: u64 = 0;
         for c in v.iter().skip(2) {
             value = (value << 1)
-                + match c {
-                    &'0' => 0,
-                    &'1' => 1,
+                + match *c {
+                    b'0' => 0,
+                    b'1' => 1,
                     _ => return LexNumberValue::Invalid,
                 }
         }
 
-        LexNumberValue::UInt(value)
+        LexNumberValue::Int(value as i64)
     }
 
-    pub fn new_hex(v: &Vec<char>) -> Self {
-        if !v.starts_with(&['0', 'x']) {
+    // 解析十六进制数字，支持小数部分及以p开头的二进制指数
+    //
+    // @param v: 数字的源码
+    //
+    // @return: 数字的值
+    pub fn new_hex(v: &[u8]) -> Self {
+        if !has_prefix(v, b'x') {
             return LexNumberValue::Invalid;
         }
 
         let mut value: u64 = 0;
-        for c in v.iter().skip(2) {
-            value = (value << 4)
-                + u64::from(match c {
-                    &('0'..='9') => u32::from(*c) - u32::from('0'),
-                    &('a'..='f') => u32::from(*c) - u32::from('a') + 10,
-                    &('A'..='F') => u32::from(*c) - u32::from('A') + 10,
-                    _ => return LexNumberValue::Invalid,
-                });
-        }
-
-        LexNumberValue::UInt(value)
-    }
+        let mut mantissa: u64 = 0;
+        let mut exp: i64 = 0;
+        let mut sticky = false;
+        let mut digits = 0;
+        let mut is_int = true;
+        let mut i = 2;
+
+        while i < v.len() {
+            let d = match (v[i], hex_digit(v[i])) {
+                (_, Some(d)) => d,
+                (b'.', None) if is_int => {
+                    is_int = false;
+                    i += 1;
+                    continue;
+                }
+                _ => break,
+            };
 
-    pub fn new_dec(v: &Vec<char>) -> Self {
-        let mut value: u64 = 0;
-        let mut frac: f64 = 0f64;
-        let mut frac_base: f64 = 0.1f64;
-        let mut is_int: bool = true;
-        for c in v.iter() {
-            if c == &'.' {
+            value = (value << 4) + d;
+            if mantissa >> 60 == 0 {
+                mantissa = (mantissa << 4) + d;
                 if !is_int {
-                    return LexNumberValue::Invalid;
+                    exp -= 4;
+                }
+            } else {
+                // 尾数已满，剩余的数字只影响舍入与指数
+                sticky |= d != 0;
+                if is_int {
+                    exp += 4;
                 }
+            }
+            digits += 1;
+            i += 1;
+        }
+
+        if digits == 0 {
+            return LexNumberValue::Invalid;
+        }
 
-                is_int = false;
-                continue;
+        if i < v.len() && (v[i] | 0x20) == b'p' {
+            is_int = false;
+            i += 1;
+
+            let neg = v.get(i) == Some(&b'-');
+            if neg || v.get(i) == Some(&b'+') {
+                i += 1;
             }
 
-            if is_int {
-                value = (value * 10)
-                    + u64::from(match c {
-                        &('0'..='9') => u32::from(*c) - u32::from('0'),
-                        _ => return LexNumberValue::Invalid,
-                    });
-            } else {
-                frac = frac
-                    + frac_base
-                        * f64::from(match c {
-                            &('0'..='9') => u32::from(*c) - u32::from('0'),
-                            _ => return LexNumberValue::Invalid,
-                        });
-                frac_base = frac_base / 10f64;
+            let start = i;
+            let mut e: i64 = 0;
+            while i < v.len() && v[i].is_ascii_digit() {
+                // 指数足够大时结果必然溢出或下溢，无需继续累加
+                e = (e * 10 + i64::from(v[i] - b'0')).min(1 << 20);
+                i += 1;
             }
+            if i == start {
+                return LexNumberValue::Invalid;
+            }
+
+            exp += if neg { -e } else { e };
         }
 
-        if is_int {
-            LexNumberValue::UInt(value)
+        if i != v.len() {
+            LexNumberValue::Invalid
+        } else if is_int {
+            // 十六进制整数溢出时按2^64取模
+            LexNumberValue::Int(value as i64)
         } else {
-            LexNumberValue::Float(value as f64 + frac)
+            LexNumberValue::Float(ldexp_rounded(mantissa, exp, sticky))
         }
     }
 
-    pub fn is_invalid(&self) -> bool {
-        match self {
-            &LexNumberValue::Invalid => true,
-            _ => false,
+    // 解析十进制数字，支持小数部分及以e开头的十进制指数
+    //
+    // @param v: 数字的源码
+    //
+    // @return: 数字的值
+    pub fn new_dec(v: &[u8]) -> Self {
+        // 十进制整数溢出时为None，此时按浮点数解析
+        let mut value: Option<i64> = Some(0);
+        let mut digits = 0;
+        let mut is_int = true;
+        let mut i = 0;
+
+        while i < v.len() && v[i].is_ascii_digit() {
+            value = value
+                .and_then(|x| x.checked_mul(10))
+                .and_then(|x| x.checked_add(i64::from(v[i] - b'0')));
+            digits += 1;
+            i += 1;
+        }
+
+        if i < v.len() && v[i] == b'.' {
+            is_int = false;
+            i += 1;
+            while i < v.len() && v[i].is_ascii_digit() {
+                digits += 1;
+                i += 1;
+            }
         }
+
+        if digits == 0 {
+            return LexNumberValue::Invalid;
+        }
+
+        if i < v.len() && (v[i] | 0x20) == b'e' {
+            is_int = false;
+            i += 1;
+            if i < v.len() && (v[i] == b'-' || v[i] == b'+') {
+                i += 1;
+            }
+
+            let start = i;
+            while i < v.len() && v[i].is_ascii_digit() {
+                i += 1;
+            }
+            if i == start {
+                return LexNumberValue::Invalid;
+            }
+        }
+
+        if i != v.len() {
+            return LexNumberValue::Invalid;
+        }
+
+        match value {
+            Some(value) if is_int => LexNumberValue::Int(value),
+            // 此时v只包含ASCII字符，且符合Rust浮点数的语法，其解析结果是正确舍入的
+            _ => match std::str::from_utf8(v).ok().and_then(|s| s.parse().ok()) {
+                Some(f) => LexNumberValue::Float(f),
+                None => LexNumberValue::Invalid,
+            },
+        }
+    }
+
+    pub fn is_invalid(&self) -> bool {
+        matches!(self, LexNumberValue::Invalid)
     }
 
     pub fn is_int(&self) -> bool {
-        match self {
-            &LexNumberValue::UInt(_) => true,
-            _ => false,
-        }
+        matches!(self, LexNumberValue::Int(_))
     }
 
-    pub fn as_int(&self) -> u64 {
-        match self {
-            &LexNumberValue::UInt(value) => value,
-            &LexNumberValue::Float(value) => value as u64,
+    pub fn as_int(&self) -> i64 {
+        match *self {
+            LexNumberValue::Int(value) | LexNumberValue::Int64(value) => value,
+            LexNumberValue::UInt64(value) => value as i64,
+            LexNumberValue::Float(value) => value as i64,
             _ => 0,
         }
     }
 
     pub fn as_float(&self) -> f64 {
-        match self {
-            &LexNumberValue::UInt(value) => value as f64,
-            &LexNumberValue::Float(value) => value,
+        match *self {
+            LexNumberValue::Int(value) | LexNumberValue::Int64(value) => value as f64,
+            LexNumberValue::UInt64(value) => value as f64,
+            LexNumberValue::Float(value) | LexNumberValue::Imaginary(value) => value,
             _ => 0f64,
         }
     }
@@ -125,7 +416,10 @@ impl LexNumberValue {
 impl PartialEq for LexNumberValue {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
-            (Self::UInt(l0), Self::UInt(r0)) => l0 == r0,
+            (Self::Int(l0), Self::Int(r0)) => l0 == r0,
+            (Self::Int64(l0), Self::Int64(r0)) => l0 == r0,
+            (Self::UInt64(l0), Self::UInt64(r0)) => l0 == r0,
+            (Self::Imaginary(l0), Self::Imaginary(r0)) => l0 == r0,
             (Self::Float(l0), Self::Float(r0)) => l0 == r0,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
@@ -134,18 +428,10 @@ impl PartialEq for LexNumberValue {
 
 #[cfg(test)]
 mod tests {
-    use super::LexNumberValue;
-
-    fn str_to_vec(s: &str) -> Vec<char> {
-        let mut ret = Vec::new();
-        for chr in s.chars() {
-            ret.push(chr);
-        }
-        ret
-    }
+    use super::{Dialect, LexNumberValue};
 
-    fn assert_binary_eq(s: &str, value: u64) {
-        let actual = LexNumberValue::new_binary(&str_to_vec(s));
+    fn assert_binary_eq(s: &str, value: i64) {
+        let actual = LexNumberValue::new_binary(s.as_bytes());
 
         assert!(!actual.is_invalid());
         assert_eq!(actual.as_int(), value);
@@ -153,14 +439,14 @@ mod tests {
     }
 
     fn assert_binary_invalid(s: &str) {
-        let actual = LexNumberValue::new_binary(&str_to_vec(s));
+        let actual = LexNumberValue::new_binary(s.as_bytes());
 
         assert!(actual.is_invalid());
         assert_eq!(actual.as_int(), 0);
     }
 
-    fn assert_hex_eq(s: &str, value: u64) {
-        let actual = LexNumberValue::new_hex(&str_to_vec(s));
+    fn assert_hex_eq(s: &str, value: i64) {
+        let actual = LexNumberValue::new_hex(s.as_bytes());
 
         assert!(!actual.is_invalid());
         assert_eq!(actual.as_int(), value);
@@ -168,14 +454,14 @@ mod tests {
     }
 
     fn assert_hex_invalid(s: &str) {
-        let actual = LexNumberValue::new_hex(&str_to_vec(s));
+        let actual = LexNumberValue::new_hex(s.as_bytes());
 
         assert!(actual.is_invalid());
         assert_eq!(actual.as_int(), 0);
     }
 
-    fn assert_dec_eq_int(s: &str, value: u64) {
-        let actual = LexNumberValue::new_dec(&str_to_vec(s));
+    fn assert_dec_eq_int(s: &str, value: i64) {
+        let actual = LexNumberValue::new_dec(s.as_bytes());
 
         assert!(!actual.is_invalid());
         assert_eq!(actual.as_int(), value);
@@ -183,7 +469,7 @@ mod tests {
     }
 
     fn assert_dec_eq_float(s: &str, value: f64) {
-        let actual = LexNumberValue::new_dec(&str_to_vec(s));
+        let actual = LexNumberValue::new_dec(s.as_bytes());
 
         assert!(!actual.is_invalid());
         assert_eq!(actual.as_float(), value);
@@ -191,7 +477,7 @@ mod tests {
     }
 
     fn assert_dec_invalid(s: &str) {
-        let actual = LexNumberValue::new_dec(&str_to_vec(s));
+        let actual = LexNumberValue::new_dec(s.as_bytes());
 
         assert!(actual.is_invalid());
         assert!(!actual.is_int());
@@ -234,4 +520,138 @@ mod tests {
         assert_dec_invalid("1a34");
         assert_dec_invalid("0.b123");
     }
+
+    #[test]
+    fn test_lex_number_dec_exponent() {
+        assert_dec_eq_float("1e10", 1e10);
+        assert_dec_eq_float("1E+2", 100f64);
+        assert_dec_eq_float("2.5e-3", 2.5e-3);
+        assert_dec_eq_float(".5e1", 5f64);
+        assert_dec_eq_float("5.", 5f64);
+        assert_dec_eq_float("3e0", 3f64);
+        assert_dec_eq_float("1e400", f64::INFINITY);
+        assert_dec_eq_float("1e-400", 0f64);
+
+        assert_dec_invalid("1e");
+        assert_dec_invalid("1e+");
+        assert_dec_invalid("1.2e3.4");
+        assert_dec_invalid(".e1");
+        assert_dec_invalid("1e2e3");
+    }
+
+    #[test]
+    fn test_lex_number_dec_rounding() {
+        assert_dec_eq_float("0.1", 0.1);
+        assert_dec_eq_float("0.3", 0.3);
+        assert_dec_eq_float("123456789.123456789", 123456789.12345679);
+        assert_dec_eq_float("2.2250738585072014e-308", f64::MIN_POSITIVE);
+        assert_dec_eq_float("1.7976931348623157e308", f64::MAX);
+        assert_dec_eq_float("4.9e-324", 5e-324);
+    }
+
+    fn assert_hex_eq_float(s: &str, value: f64) {
+        let actual = LexNumberValue::new_hex(s.as_bytes());
+
+        assert!(!actual.is_invalid());
+        assert!(!actual.is_int());
+        assert_eq!(actual.as_float().to_bits(), value.to_bits(), "{}", s);
+    }
+
+    #[test]
+    fn test_lex_number_hex_float() {
+        assert_hex_eq_float("0x1.8p3", 12f64);
+        assert_hex_eq_float("0xA.8p0", 10.5);
+        assert_hex_eq_float("0x.1p4", 1f64);
+        assert_hex_eq_float("0x1.8", 1.5);
+        assert_hex_eq_float("0x8.", 8f64);
+        assert_hex_eq_float("0X1P+4", 16f64);
+        assert_hex_eq_float("0x1p-2", 0.25);
+        assert_hex_eq_float("0x0.0p0", 0f64);
+        assert_hex_eq("0X10", 16);
+
+        assert_hex_invalid("0x");
+        assert_hex_invalid("0x.p1");
+        assert_hex_invalid("0x1p");
+        assert_hex_invalid("0x1p+");
+        assert_hex_invalid("0x1.2.3");
+        assert_hex_invalid("0xp1");
+    }
+
+    #[test]
+    fn test_lex_number_hex_float_rounding() {
+        assert_hex_eq_float("0x1.fffffffffffffp1023", f64::MAX);
+        assert_hex_eq_float("0x1.fffffffffffff8p1023", f64::INFINITY);
+        assert_hex_eq_float("0x1p1024", f64::INFINITY);
+        assert_hex_eq_float("0x1p-1022", f64::MIN_POSITIVE);
+        assert_hex_eq_float("0x1p-1074", f64::from_bits(1));
+        assert_hex_eq_float("0x1p-1075", 0f64);
+        assert_hex_eq_float("0x1.00001p-1075", f64::from_bits(1));
+        assert_hex_eq_float("0x3p-1075", f64::from_bits(2));
+        assert_hex_eq_float("0x0.fffffffffffff8p-1022", f64::MIN_POSITIVE);
+        assert_hex_eq_float("0x1p-2000", 0f64);
+        assert_hex_eq_float("0x123456789abcdef01.0", 0x123456789abcdef01u128 as f64);
+        assert_hex_eq_float("0x1.00000000000008p0", 1f64);
+        assert_hex_eq_float("0x1.000000000000080000000001p0", 1f64 + f64::EPSILON);
+        assert_hex_eq_float("0x1.00000000000018p0", 1f64 + 2f64 * f64::EPSILON);
+    }
+
+    #[test]
+    fn test_lex_number_int_overflow() {
+        assert_dec_eq_int("9223372036854775807", i64::MAX);
+        assert_dec_eq_float("9223372036854775808", 9223372036854775808f64);
+        assert_dec_eq_float("18446744073709551616", 18446744073709551616f64);
+        assert_dec_eq_float("123456789012345678901234567890", 1.2345678901234568e29);
+
+        assert_hex_eq("0x7fffffffffffffff", i64::MAX);
+        assert_hex_eq("0x8000000000000000", i64::MIN);
+        assert_hex_eq("0xffffffffffffffff", -1);
+        assert_hex_eq("0x10000000000000000", 0);
+        assert_hex_eq("0x1ffffffffffffffff", -1);
+
+        assert_binary_eq(
+            "0b1111111111111111111111111111111111111111111111111111111111111111",
+            -1,
+        );
+        assert_binary_invalid("0b");
+    }
+
+    #[test]
+    fn test_lex_number_luajit_suffix() {
+        let jit = |s: &str| LexNumberValue::new_luajit(s.as_bytes());
+
+        assert!(jit("123LL") == LexNumberValue::Int64(123));
+        assert!(jit("123ll") == LexNumberValue::Int64(123));
+        assert!(jit("0xffffffffffffffffLL") == LexNumberValue::Int64(-1));
+        assert!(jit("0xffULL") == LexNumberValue::UInt64(0xff));
+        assert!(jit("18446744073709551615ull") == LexNumberValue::UInt64(u64::MAX));
+        assert!(jit("0b101ULL") == LexNumberValue::UInt64(5));
+        assert!(jit("2.5i") == LexNumberValue::Imaginary(2.5));
+        assert!(jit("3I") == LexNumberValue::Imaginary(3f64));
+        assert!(jit("0x1p4i") == LexNumberValue::Imaginary(16f64));
+        assert!(jit("1e2i") == LexNumberValue::Imaginary(100f64));
+        assert!(jit("42") == LexNumberValue::Int(42));
+
+        assert!(jit("18446744073709551616ULL").is_invalid());
+        assert!(jit("1.5LL").is_invalid());
+        assert!(jit("1e3ULL").is_invalid());
+        assert!(jit("LL").is_invalid());
+        assert!(jit("0xLL").is_invalid());
+        assert!(jit("1L").is_invalid());
+        assert!(jit("1ii").is_invalid());
+        assert!(jit("1LLU").is_invalid());
+    }
+
+    #[test]
+    fn test_lex_number_dialect() {
+        let num = |v: &str, dialect| LexNumberValue::new_dialect(v.as_bytes(), dialect);
+
+        assert!(num("0b11", Dialect::Lua54).is_invalid());
+        assert!(num("0b11", Dialect::LuaJit) == LexNumberValue::Float(3.0));
+        assert!(num("0x1.8", Dialect::Lua51).is_invalid());
+        assert!(num("0x1.8", Dialect::Lua52) == LexNumberValue::Float(1.5));
+        assert!(num("7", Dialect::Lua51) == LexNumberValue::Float(7.0));
+        assert!(num("7", Dialect::Lua53) == LexNumberValue::Int(7));
+        assert!(num("7LL", Dialect::LuaJit) == LexNumberValue::Int64(7));
+        assert!(num("7LL", Dialect::Lua54).is_invalid());
+    }
 }
@@ -0,0 +1,170 @@
+use std::borrow::Cow;
+use std::io::{self, BufRead, Read};
+
+// 每次从Read中读取的字节数
+const READ_CHUNK_SIZE: usize = 4096;
+
+// 源码读取器，词法分析器通过它分段拉取Lua源码
+//
+// 返回None或空的源码段表示源码已结束，与Lua中load(func)的约定一致
+pub trait LexReader<'src_lt> {
+    // 读取下一段源码
+    //
+    // @return: 下一段源码
+    fn read(&mut self) -> io::Result<Option<Cow<'src_lt, [u8]>>>;
+}
+
+impl<'src_lt, F> LexReader<'src_lt> for F
+where
+    F: FnMut() -> Option<Vec<u8>>,
+{
+    fn read(&mut self) -> io::Result<Option<Cow<'src_lt, [u8]>>> {
+        Ok(self().map(Cow::Owned))
+    }
+}
+
+// 从std::io::Read中按固定大小分段读取源码
+pub struct IoReader<R> {
+    inner: R,
+}
+
+impl<R: Read> IoReader<R> {
+    // 构造新的IoReader
+    //
+    // @param inner: 被读取的对象
+    //
+    // @return: IoReader
+    pub fn new(inner: R) -> Self {
+        IoReader { inner }
+    }
+}
+
+impl<'src_lt, R: Read> LexReader<'src_lt> for IoReader<R> {
+    fn read(&mut self) -> io::Result<Option<Cow<'src_lt, [u8]>>> {
+        let mut chunk = vec![0; READ_CHUNK_SIZE];
+        loop {
+            match self.inner.read(&mut chunk) {
+                Ok(0) => return Ok(None),
+                Ok(n) => {
+                    chunk.truncate(n);
+                    return Ok(Some(Cow::Owned(chunk)));
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+// 从std::io::BufRead中按其内部缓冲区分段读取源码
+pub struct BufReadReader<R> {
+    inner: R,
+}
+
+impl<R: BufRead> BufReadReader<R> {
+    // 构造新的BufReadReader
+    //
+    // @param inner: 被读取的对象
+    //
+    // @return: BufReadReader
+    pub fn new(inner: R) -> Self {
+        BufReadReader { inner }
+    }
+}
+
+impl<'src_lt, R: BufRead> LexReader<'src_lt> for BufReadReader<R> {
+    fn read(&mut self) -> io::Result<Option<Cow<'src_lt, [u8]>>> {
+        let chunk = loop {
+            match self.inner.fill_buf() {
+                Ok(buf) => break buf.to_vec(),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        self.inner.consume(chunk.len());
+
+        Ok(if chunk.is_empty() {
+            None
+        } else {
+            Some(Cow::Owned(chunk))
+        })
+    }
+}
+
+// 从迭代器中逐段读取源码，迭代器产生的源码段可以是借用的，也可以是拥有所有权的；
+// 迭代器结束时源码结束
+pub struct ChunkReader<I> {
+    inner: I,
+}
+
+impl<I> ChunkReader<I> {
+    // 构造新的ChunkReader
+    //
+    // @param inner: 产生源码段的迭代器
+    //
+    // @return: ChunkReader
+    pub fn new(inner: I) -> Self {
+        ChunkReader { inner }
+    }
+}
+
+impl<'src_lt, I> LexReader<'src_lt> for ChunkReader<I>
+where
+    I: Iterator,
+    I::Item: Into<Cow<'src_lt, [u8]>>,
+{
+    fn read(&mut self) -> io::Result<Option<Cow<'src_lt, [u8]>>> {
+        // 迭代器中的空段不表示源码结束，直接跳过
+        Ok(self
+            .inner
+            .by_ref()
+            .map(Into::into)
+            .find(|chunk: &Cow<'src_lt, [u8]>| !chunk.is_empty()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all<'a>(mut reader: impl LexReader<'a>) -> Vec<u8> {
+        let mut ret = Vec::new();
+        while let Some(chunk) = reader.read().unwrap() {
+            if chunk.is_empty() {
+                break;
+            }
+            ret.extend_from_slice(&chunk);
+        }
+        ret
+    }
+
+    #[test]
+    fn fn_reader() {
+        let mut chunks = vec![b"b = 2".to_vec(), b"a = 1; ".to_vec()];
+        assert_eq!(read_all(move || chunks.pop()), b"a = 1; b = 2");
+    }
+
+    #[test]
+    fn io_reader() {
+        let src = vec![b'x'; READ_CHUNK_SIZE * 2 + 3];
+        let mut reader = IoReader::new(&src[..]);
+
+        assert_eq!(reader.read().unwrap().unwrap().len(), READ_CHUNK_SIZE);
+        assert_eq!(read_all(reader).len(), READ_CHUNK_SIZE + 3);
+    }
+
+    #[test]
+    fn buf_read_reader() {
+        let src = io::BufReader::with_capacity(3, &b"local x"[..]);
+        assert_eq!(read_all(BufReadReader::new(src)), b"local x");
+    }
+
+    #[test]
+    fn chunk_reader() {
+        let mut reader = ChunkReader::new(vec![&b"ab"[..], &b"cd"[..]].into_iter());
+        assert!(matches!(reader.read().unwrap(), Some(Cow::Borrowed(b"ab"))));
+
+        let chunks = vec![b"ab".to_vec(), Vec::new(), b"cd".to_vec()];
+        assert_eq!(read_all(ChunkReader::new(chunks.into_iter())), b"abcd");
+    }
+}
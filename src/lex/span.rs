@@ -0,0 +1,127 @@
+use super::token::LexToken;
+
+// 源码中的一个位置，offset与column均以字节计
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Position {
+    pub offset: usize,
+    pub line: u32,
+    pub column: u32,
+}
+
+// 源码中的一段区间，start为起始位置，end为结束位置的下一个字符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+// 携带源码区间的Token
+#[derive(Debug, PartialEq)]
+pub struct SpannedToken<'src_lt> {
+    pub token: LexToken<'src_lt>,
+    pub span: Span,
+}
+
+impl Position {
+    // 构造新的Position
+    //
+    // @param offset: 字节偏移量
+    // @param line: 行号
+    // @param column: 列号
+    //
+    // @return: Position
+    pub fn new(offset: usize, line: u32, column: u32) -> Self {
+        Position {
+            offset,
+            line,
+            column,
+        }
+    }
+}
+
+impl Span {
+    // 构造新的Span
+    //
+    // @param start: 起始位置
+    // @param end: 结束位置
+    //
+    // @return: Span
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+
+    // 区间所包含的字节数
+    //
+    // @return: 字节数
+    pub fn len(&self) -> usize {
+        self.end.offset - self.start.offset
+    }
+
+    // 区间是否为空
+    //
+    // @return: 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 合并两个区间，得到覆盖两者的区间
+    //
+    // @param other: 另一个区间
+    //
+    // @return: 合并后的区间
+    pub fn merge(&self, other: &Span) -> Span {
+        Span {
+            start: if self.start.offset <= other.start.offset {
+                self.start
+            } else {
+                other.start
+            },
+            end: if self.end.offset >= other.end.offset {
+                self.end
+            } else {
+                other.end
+            },
+        }
+    }
+}
+
+impl<'src_lt> SpannedToken<'src_lt> {
+    // 构造新的SpannedToken
+    //
+    // @param token: Token
+    // @param span: Token在源码中的区间
+    //
+    // @return: SpannedToken
+    pub fn new(token: LexToken<'src_lt>, span: Span) -> Self {
+        SpannedToken { token, span }
+    }
+
+    // 将借用源码的SpannedToken转化为独立拥有其内容的SpannedToken
+    //
+    // @return: 不再借用源码的SpannedToken
+    pub fn into_owned(self) -> SpannedToken<'static> {
+        SpannedToken::new(self.token.into_owned(), self.span)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_len() {
+        let span = Span::new(Position::new(3, 1, 4), Position::new(8, 2, 2));
+        assert_eq!(span.len(), 5);
+        assert!(!span.is_empty());
+        assert!(Span::default().is_empty());
+    }
+
+    #[test]
+    fn span_merge() {
+        let a = Span::new(Position::new(0, 1, 1), Position::new(3, 1, 4));
+        let b = Span::new(Position::new(5, 1, 6), Position::new(9, 2, 1));
+
+        assert_eq!(a.merge(&b), Span::new(a.start, b.end));
+        assert_eq!(b.merge(&a), Span::new(a.start, b.end));
+    }
+}
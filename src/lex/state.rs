@@ -1,44 +1,210 @@
 use crate::is_alpha;
-use crate::toolbox::chr::{BITS, DIGIT, IDENT};
+use crate::toolbox::chr::{utf8_esc, BITS, DIGIT, IDENT, PUNCT, SPACE};
 
+use super::dialect::Dialect;
+use super::error::{LexError, LexErrorKind, LexEscapeError};
+use super::reader::{BufReadReader, ChunkReader, IoReader, LexReader};
+use super::span::{Position, Span, SpannedToken};
+use super::symbol::{Interner, MAX_SHORT_LEN};
 use super::token::{LexNumberValue, LexToken};
-use std::str::Chars;
+use super::trivia::{LosslessToken, Trivia, TriviaKind};
+use std::borrow::Cow;
+use std::io::{self, BufRead, Read};
+
+// 一次扫描的结果，可能是Token，也可能是一段空白或注释
+enum ScanItem<'src_lt> {
+    Token(LexToken<'src_lt>),
+    Trivia(TriviaKind),
+}
+
+// 字符作为Token首字符时的类别，决定read_item如何扫描
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    // 字母、下划线及非ASCII字节，开始一个名字或关键字
+    Name,
+    Digit,
+    Newline,
+    // 换行以外的空白
+    Space,
+    // 运算符、分隔符、字符串及注释的开始
+    Punct,
+    // 控制字符等不能开始Token的字符
+    Other,
+}
+
+// 由BITS生成的字符类别表
+static CHAR_CLASSES: [CharClass; 256] = char_classes();
+
+// 生成字符类别表
+//
+// @return: 以字符为下标的类别表
+const fn char_classes() -> [CharClass; 256] {
+    let mut classes = [CharClass::Other; 256];
+    let mut c = 0;
+    while c < classes.len() {
+        let bits = BITS[1 + c];
+        classes[c] = if bits & DIGIT != 0 {
+            CharClass::Digit
+        } else if bits & IDENT != 0 {
+            CharClass::Name
+        } else if c == b'\n' as usize || c == b'\r' as usize {
+            CharClass::Newline
+        } else if bits & SPACE != 0 {
+            CharClass::Space
+        } else if bits & PUNCT != 0 {
+            CharClass::Punct
+        } else {
+            CharClass::Other
+        };
+        c += 1;
+    }
+
+    classes
+}
 
 // 词法分析状态器
 pub struct LexStatus<'src_lt> {
-    more_fn: fn() -> Option<&'src_lt str>,
-    src: &'src_lt str,
-    src_p: Chars<'src_lt>,
-    buf: Vec<char>,
-    chr: Option<char>,
+    reader: Box<dyn LexReader<'src_lt> + 'src_lt>,
+    src: Cow<'src_lt, [u8]>,
+    src_p: usize,
+    eof: bool,
+    read_error: Option<io::ErrorKind>,
+    buf: Vec<u8>,
+    mark: Option<usize>,
+    chr: Option<u8>,
     line_number: u32,
+    column: u32,
+    offset: usize,
+    token_start: Position,
+    dialect: Dialect,
+    lossless: bool,
+    raw: Vec<u8>,
+    skip_header: bool,
+    header: Vec<Trivia>,
+    interp: Vec<u32>,
+    recovery: bool,
+    interner: Interner,
 }
 
 impl<'src_lt> LexStatus<'src_lt> {
-    // 构造新的LexStatus
+    // 构造新的LexStatus，源码可以是任意字节序列
     //
     // @param src: 传入的Lua源码
     //
     // @return: LexStatus
-    pub fn new(src: &'src_lt str) -> Self {
+    pub fn new<S: AsRef<[u8]> + ?Sized>(src: &'src_lt S) -> Self {
+        let mut lex = LexStatus::from_reader(|| None);
+        lex.src = Cow::Borrowed(src.as_ref());
+
+        lex
+    }
+
+    // 构造一个从读取器中分段拉取源码的LexStatus
+    //
+    // @param reader: 源码读取器
+    //
+    // @return: LexStatus
+    pub fn from_reader<R: LexReader<'src_lt> + 'src_lt>(reader: R) -> Self {
         LexStatus {
-            more_fn: || None,
-            src,
-            src_p: src.chars(),
+            reader: Box::new(reader),
+            src: Cow::Borrowed(&[]),
+            src_p: 0,
+            eof: false,
+            read_error: None,
             buf: Vec::new(),
+            mark: None,
             chr: None,
-            line_number: 0,
+            line_number: 1,
+            column: 1,
+            offset: 0,
+            token_start: Position::default(),
+            dialect: Dialect::default(),
+            lossless: false,
+            raw: Vec::new(),
+            skip_header: false,
+            header: Vec::new(),
+            interp: Vec::new(),
+            recovery: false,
+            interner: Interner::new(),
+        }
+    }
+
+    // 构造一个从闭包中分段拉取源码的LexStatus，闭包返回None或空段表示源码结束
+    //
+    // @param f: 返回下一段源码的闭包
+    //
+    // @return: LexStatus
+    pub fn from_fn<F: FnMut() -> Option<Vec<u8>> + 'src_lt>(f: F) -> Self {
+        LexStatus::from_reader(f)
+    }
+
+    // 构造一个从std::io::Read中分段读取源码的LexStatus
+    //
+    // @param r: 被读取的对象
+    //
+    // @return: LexStatus
+    pub fn from_read<R: Read + 'src_lt>(r: R) -> Self {
+        LexStatus::from_reader(IoReader::new(r))
+    }
+
+    // 构造一个从std::io::BufRead中分段读取源码的LexStatus
+    //
+    // @param r: 被读取的对象
+    //
+    // @return: LexStatus
+    pub fn from_buf_read<R: BufRead + 'src_lt>(r: R) -> Self {
+        LexStatus::from_reader(BufReadReader::new(r))
+    }
+
+    // 构造一个从迭代器中逐段获取源码的LexStatus
+    //
+    // @param chunks: 产生源码段的迭代器
+    //
+    // @return: LexStatus
+    pub fn from_chunks<I>(chunks: I) -> Self
+    where
+        I: IntoIterator,
+        I::IntoIter: 'src_lt,
+        I::Item: Into<Cow<'src_lt, [u8]>>,
+    {
+        LexStatus::from_reader(ChunkReader::new(chunks.into_iter()))
+    }
+
+    // 从读取器中读取下一段非空的Lua源码，读取结束或失败时返回None
+    //
+    // @return: 下一段源码
+    fn read_chunk(&mut self) -> Option<Cow<'src_lt, [u8]>> {
+        if self.eof {
+            return None;
+        }
+
+        match self.reader.read() {
+            Ok(Some(src)) if !src.is_empty() => Some(src),
+            Ok(_) => {
+                self.eof = true;
+                None
+            }
+            Err(e) => {
+                self.eof = true;
+                self.read_error = Some(e.kind());
+                None
+            }
         }
     }
 
-    // 从more_fn中获取下一段Lua源码
+    // 从读取器中获取下一段Lua源码
     //
-    // @return: Lua源码是否已结束
+    // @return: 是否获取到了新的源码
     fn more(&mut self) -> bool {
-        match (self.more_fn)() {
+        match self.read_chunk() {
             Some(src) => {
+                // 正在扫描的Token跨越了源码段，已扫描的部分复制到缓存中
+                if let Some(start) = self.mark {
+                    self.buf.extend_from_slice(&self.src[start..]);
+                    self.mark = Some(0);
+                }
                 self.src = src;
-                self.src_p = self.src.chars();
+                self.src_p = 0;
 
                 true
             }
@@ -46,80 +212,267 @@ impl<'src_lt> LexStatus<'src_lt> {
         }
     }
 
-    // 从Lua源码指针处获取一个字符，并将源码指针向下移动一位。
+    // 从Lua源码指针处获取一个字节，并将源码指针向下移动一位。
     // 如果源码指针指向结束，则调用more方法加载下一段源码。
     // 如果more方法返回源码读取结束，则返回None
     //
-    // @return: 返回一个字符
-    fn next(&mut self) -> Option<char> {
-        self.chr = match self.src_p.next() {
-            Some(c) => Some(c),
-            None => {
-                if self.more() {
-                    self.src_p.next()
-                } else {
-                    None
-                }
+    // @return: 返回一个字节
+    fn next(&mut self) -> Option<u8> {
+        if let Some(c) = self.chr {
+            self.offset += 1;
+            self.column += 1;
+
+            if self.lossless {
+                self.raw.push(c);
             }
-        };
+        }
+
+        if self.src_p >= self.src.len() && !self.more() {
+            self.chr = None;
+        } else {
+            self.chr = Some(self.src[self.src_p]);
+            self.src_p += 1;
+        }
 
         self.chr
     }
 
+    // 查看当前字符之后的一个字节，但不移动源码指针
+    //
+    // @return: 当前字符之后的字节
+    fn peek_next(&mut self) -> Option<u8> {
+        if self.src_p >= self.src.len() && !self.more() {
+            return None;
+        }
+
+        Some(self.src[self.src_p])
+    }
+
+    // 查看当前字符之后的至多n个字节，但不移动源码指针。
+    // 当前源码段中不足n个字节时，将之后的源码段拼接到当前段的末尾
+    //
+    // @param n: 查看的字节数
+    //
+    // @return: 当前字符之后的字节
+    fn peek_bytes(&mut self, n: usize) -> &[u8] {
+        while self.src.len() - self.src_p < n {
+            match self.read_chunk() {
+                Some(src) => self.src.to_mut().extend_from_slice(&src),
+                None => break,
+            }
+        }
+
+        let end = self.src.len().min(self.src_p + n);
+        &self.src[self.src_p..end]
+    }
+
     // 保存一个字符到缓存中
     //
     // @param c: 待保存的字符
-    fn save(&mut self, c: char) {
+    fn save(&mut self, c: u8) {
         self.buf.push(c)
     }
 
-    // 保存当前指向的字符到缓存中，并移动源码指针
+    // 标记当前字符为一个Token的开始，此后扫描过的源码无需逐字节保存，
+    // 可以直接从源码中取得
+    fn mark(&mut self) {
+        self.buf.clear();
+        self.mark = match self.chr {
+            // 当前字符属于上一段源码(已通过peek_next加载了下一段)
+            Some(c) if self.src_p == 0 => {
+                self.buf.push(c);
+                Some(0)
+            }
+            Some(_) => Some(self.src_p - 1),
+            None => Some(self.src_p),
+        };
+    }
+
+    // 获取当前字符在源码段中的下标，即已扫描部分的结束位置
     //
-    // @return: 返回一个字符
-    fn save_next(&mut self) -> Option<char> {
-        if let Some(c) = self.chr {
-            self.save(c);
+    // @return: 下标
+    fn mark_end(&self) -> usize {
+        match self.chr {
+            Some(_) => self.src_p - 1,
+            None => self.src_p,
+        }
+    }
+
+    // 获取从标记处到当前字符(不含)的原始源码
+    //
+    // @return: 原始源码
+    fn lexeme(&self) -> Cow<'_, [u8]> {
+        let start = self.mark.unwrap_or_else(|| self.mark_end());
+        let src = &self.src[start..self.mark_end()];
+        if self.buf.is_empty() {
+            Cow::Borrowed(src)
+        } else {
+            Cow::Owned([&self.buf[..], src].concat())
+        }
+    }
+
+    // 获取从标记处到当前字符(不含)的原始源码的长度
+    //
+    // @return: 长度
+    fn lexeme_len(&self) -> usize {
+        let start = self.mark.unwrap_or_else(|| self.mark_end());
+        self.buf.len() + self.mark_end() - start
+    }
+
+    // 取出从标记处到当前字符(不含)的原始源码并清除标记；
+    // 源码是借用的且Token未跨越源码段时不复制
+    //
+    // @return: 原始源码
+    fn take_lexeme(&mut self) -> Cow<'src_lt, [u8]> {
+        let end = self.mark_end();
+        let start = self.mark.take().unwrap_or(end);
+
+        if self.buf.is_empty() {
+            match &self.src {
+                Cow::Borrowed(src) => {
+                    let src: &'src_lt [u8] = src;
+                    Cow::Borrowed(&src[start..end])
+                }
+                Cow::Owned(src) => Cow::Owned(src[start..end].to_vec()),
+            }
+        } else {
+            self.buf.extend_from_slice(&self.src[start..end]);
+            Cow::Owned(std::mem::take(&mut self.buf))
+        }
+    }
+
+    // 截取原始源码中的一段，借用的源码截取后仍是借用的
+    //
+    // @param v: 原始源码
+    // @param start: 起始下标
+    // @param end: 结束下标(不含)
+    //
+    // @return: 截取的源码
+    fn slice_lexeme(v: Cow<'src_lt, [u8]>, start: usize, end: usize) -> Cow<'src_lt, [u8]> {
+        match v {
+            Cow::Borrowed(v) => Cow::Borrowed(&v[start..end]),
+            Cow::Owned(mut v) => {
+                v.truncate(end);
+                v.drain(..start);
+                Cow::Owned(v)
+            }
+        }
+    }
+
+    // 判断字符是否属于某一类字符，源码结束时不属于任何一类
+    //
+    // @param c: 待判断的字符
+    // @param bits: 字符类别
+    //
+    // @return: 是否属于该类字符
+    fn is_chr(c: Option<u8>, bits: u8) -> bool {
+        match c {
+            Some(c) => is_alpha!(c, bits),
+            None => false,
+        }
+    }
+
+    // 获取字符作为Token首字符时的类别
+    //
+    // @param c: 待判断的字符
+    //
+    // @return: 字符的类别，源码结束时返回None
+    fn class(c: Option<u8>) -> Option<CharClass> {
+        c.map(|c| CHAR_CLASSES[c as usize])
+    }
+
+    // 构造一个词法错误，出错处附近的源码取自正在扫描的Token，没有Token时取自缓存
+    //
+    // @param kind: 错误类型
+    //
+    // @return: 词法错误
+    fn error(&mut self, kind: LexErrorKind) -> LexError {
+        let near = if self.mark.is_some() {
+            String::from_utf8_lossy(&self.lexeme()).into_owned()
+        } else {
+            self.buf_to_string()
+        };
+        LexError::new(kind, self.line_number, self.column, near)
+    }
+
+    // 构造一个在源码结束处发生的词法错误，若源码因读取失败而结束，则报告读取错误
+    //
+    // @param kind: 错误类型
+    //
+    // @return: 词法错误
+    fn error_eof(&mut self, kind: LexErrorKind) -> LexError {
+        let kind = match self.read_error.take() {
+            Some(e) => LexErrorKind::ReadFailed(e),
+            None => kind,
+        };
+
+        self.buf.clear();
+        self.mark = None;
+        LexError::new(kind, self.line_number, self.column, String::from("<eof>"))
+    }
+
+    // 构造一个转义序列错误，出错的字符会一并作为出错处附近的源码
+    //
+    // @param e: 转义序列错误类型
+    //
+    // @return: 词法错误
+    fn escape_error(&mut self, e: LexEscapeError) -> LexError {
+        // 恢复模式下不越过定界符及换行，使字符串仍能在原处结束，报告的错误与非恢复模式相同
+        if let Some(c @ (b'\'' | b'"' | b'`' | b'\n' | b'\r')) = self.chr {
+            if self.recovery {
+                let mut near = self.lexeme().into_owned();
+                near.push(c);
+                let near = String::from_utf8_lossy(&near).into_owned();
+                let kind = LexErrorKind::InvalidEscape(e);
+                return LexError::new(kind, self.line_number, self.column + 1, near);
+            }
+        }
+
+        if self.chr.is_some() {
+            self.next();
         }
 
-        self.next()
+        self.error(LexErrorKind::InvalidEscape(e))
     }
 
     // 将接下来一段源码解析为Number
     //
     // @return: 返回一个Token
-    pub fn number(&mut self) -> Option<LexToken> {
-        let mut xp = 'e';
-        let mut c = self.chr.unwrap();
+    pub fn number(&mut self) -> Result<LexToken<'src_lt>, LexError> {
+        let mut xp = b'e';
+        let mut c = self.chr.unwrap_or(b'0');
 
-        if c == '0' {
-            if let Some(next) = self.save_next() {
-                if (u32::from(next) | 0x20) == u32::from('x') {
-                    xp = 'p';
+        self.mark();
+        if c == b'0' {
+            if let Some(next) = self.next() {
+                if (next | 0x20) == b'x' {
+                    xp = b'p';
                 }
             }
         }
 
         loop {
             if let Some(t) = self.chr {
-                if is_alpha!(t, IDENT)
-                    || t == '.'
-                    || ((t == '-' || t == '+') && (u32::from(c) | 0x20) == u32::from(xp))
+                if Self::is_chr(Some(t), IDENT)
+                    || t == b'.'
+                    || ((t == b'-' || t == b'+') && (c | 0x20) == xp)
                 {
                     c = t;
-                    self.save_next();
+                    self.next();
                     continue;
                 }
             }
             break;
         }
 
-        let value = LexNumberValue::new(&self.buf);
-        self.buf.clear();
+        let value = LexNumberValue::new_dialect(&self.lexeme(), self.dialect);
 
         if value.is_invalid() {
-            None
+            Err(self.error(LexErrorKind::MalformedNumber))
         } else {
-            Some(LexToken::Number(value))
+            self.mark = None;
+            self.buf.clear();
+            Ok(LexToken::Number(value))
         }
     }
 
@@ -130,8 +483,8 @@ impl<'src_lt> LexStatus<'src_lt> {
         let mut count: i32 = 0;
         let s = self.chr;
 
-        while self.save_next().eq(&Some('=')) && count < 0x20000000 {
-            count = count + 1;
+        while self.next().eq(&Some(b'=')) && count < 0x20000000 {
+            count += 1;
         }
 
         if self.chr == s {
@@ -145,7 +498,7 @@ impl<'src_lt> LexStatus<'src_lt> {
     //
     // @return: 是否处于源码中的一行
     fn is_eol(&self) -> bool {
-        self.chr.eq(&Some('\n')) || self.chr.eq(&Some('\r'))
+        self.chr.eq(&Some(b'\n')) || self.chr.eq(&Some(b'\r'))
     }
 
     // 向下一行
@@ -158,252 +511,942 @@ impl<'src_lt> LexStatus<'src_lt> {
             self.next();
         }
 
-        self.line_number = self.line_number + 1;
+        self.line_number += 1;
+        self.column = 1;
     }
 
-    // 将缓冲区的内容转化为字符串
+    // 将缓冲区的内容转化为字符串，非UTF-8的字节序列以U+FFFD代替
     //
     // @return: 字符串
     fn buf_to_string(&mut self) -> String {
-        let mut s = String::new();
-        for c in self.buf.iter() {
-            s.push(*c);
-        }
+        let s = String::from_utf8_lossy(&self.buf).into_owned();
         self.buf.clear();
 
         s
     }
 
-    // 接下来的一段源码解析为LongString
+    // 接下来的一段源码解析为LongString，调用前需在左长括号处标记
     //
-    // @return: 返回一个Token
-    fn longstring(&mut self, sep: i32) -> Option<LexToken> {
-        self.save_next();
+    // @param sep: 长括号中等于号的个数
+    // @param comment: 是否为长注释
+    //
+    // @return: 长括号中的内容，长注释返回空
+    fn longstring(&mut self, sep: i32, comment: bool) -> Result<Cow<'src_lt, [u8]>, LexError> {
+        let line = self.line_number;
+
+        self.next();
         if self.is_eol() {
             self.new_line();
         }
+        let start = self.lexeme_len();
+        let mut normalize = false;
 
         loop {
             match self.chr {
-                None => return None,
-                Some(']') => {
+                None => {
+                    return Err(self.error_eof(if comment {
+                        LexErrorKind::UnfinishedLongComment(line)
+                    } else {
+                        LexErrorKind::UnfinishedLongString(line)
+                    }))
+                }
+                Some(b']') => {
                     if self.skip_eq() == sep {
-                        self.save_next();
+                        self.next();
                         break;
                     }
                 }
-                Some('\r') | Some('\n') => {
-                    self.save('\n');
+                Some(c) if c == b'\r' || c == b'\n' => {
+                    let offset = self.offset;
                     self.new_line();
+                    normalize |= c == b'\r' || self.offset - offset > 1;
                 }
                 _ => {
-                    self.save_next();
+                    self.next();
                 }
             }
         }
 
-        Some(LexToken::Str(self.buf_to_string()))
-    }
+        if comment {
+            self.mark = None;
+            self.buf.clear();
+            return Ok(Cow::Borrowed(&[]));
+        }
 
-    fn string(&mut self) -> Option<LexToken> {
-        let delim = self.chr;
-        self.save_next();
+        // 去掉首尾的长括号
+        let raw = self.take_lexeme();
+        let end = raw.len() - (sep as usize + 2);
+        let value = Self::slice_lexeme(raw, start, end);
+        if !normalize {
+            return Ok(value);
+        }
 
-        while self.chr != delim {
-            match self.chr {
-                Some('\\') => {
-                    let val: char = match self.next() {
-                        Some('n') => '\n',
-                        Some('r') => '\r',
-                        Some('t') => '\t',
-                        _ => return None,
-                    };
-
-                    self.save(val);
-                    self.next();
-                }
-                Some('\n') | Some('\r') => return None,
-                None => return None,
-                _ => {
-                    self.save_next();
+        // 与Lua一致，长字符串中的\r\n、\n\r及\r均转换为\n
+        let mut ret = Vec::with_capacity(value.len());
+        let mut iter = value.iter().peekable();
+        while let Some(&c) = iter.next() {
+            if c == b'\r' || c == b'\n' {
+                ret.push(b'\n');
+                if let Some(&&n) = iter.peek() {
+                    if (n == b'\r' || n == b'\n') && n != c {
+                        iter.next();
+                    }
                 }
+            } else {
+                ret.push(c);
             }
         }
-        self.save_next();
 
-        Some(LexToken::Str(self.buf_to_string()))
+        Ok(Cow::Owned(ret))
     }
 
-    pub fn setup(&mut self) {
+    // 读取\xXX转义序列，当前字符指向x
+    //
+    // @return: 转义得到的字节
+    fn read_hex_esc(&mut self) -> Result<u8, LexError> {
+        let mut r: u32 = 0;
+        for _ in 0..2 {
+            match self.next().and_then(|c| char::from(c).to_digit(16)) {
+                Some(d) => r = (r << 4) + d,
+                None => return Err(self.escape_error(LexEscapeError::HexDigitExpected)),
+            }
+        }
         self.next();
+
+        Ok(r as u8)
     }
 
-    fn name(&mut self) -> Option<LexToken> {
-        let tok = self.buf_to_string();
-        Some(match tok.as_str() {
-            "and" => LexToken::And,
-            "break" => LexToken::Break,
-            "do" => LexToken::Do,
-            "else" => LexToken::Else,
-            "elseif" => LexToken::ElseIf,
-            "end" => LexToken::End,
-            "false" => LexToken::False,
-            "for" => LexToken::For,
-            "function" => LexToken::Function,
-            "goto" => LexToken::Goto,
-            "if" => LexToken::If,
-            "in" => LexToken::In,
-            "local" => LexToken::Local,
-            "nil" => LexToken::Nil,
-            "not" => LexToken::Not,
-            "or" => LexToken::Or,
-            "repeat" => LexToken::Repeat,
-            "return" => LexToken::Return,
-            "then" => LexToken::Then,
-            "true" => LexToken::True,
-            "util" => LexToken::Util,
-            "while" => LexToken::While,
-            _ => LexToken::Name(tok),
-        })
+    // 读取\u{XXX}转义序列，当前字符指向u
+    //
+    // @return: 转义得到的码点，最大值由方言决定
+    fn read_utf8_esc(&mut self) -> Result<u32, LexError> {
+        let max = self.dialect.max_utf8_escape();
+
+        if self.next() != Some(b'{') {
+            return Err(self.escape_error(LexEscapeError::MissingLeftBrace));
+        }
+
+        let mut r: u32 = match self.next().and_then(|c| char::from(c).to_digit(16)) {
+            Some(d) => d,
+            None => return Err(self.escape_error(LexEscapeError::HexDigitExpected)),
+        };
+        while let Some(d) = self.next().and_then(|c| char::from(c).to_digit(16)) {
+            if r > (max >> 4) {
+                return Err(self.escape_error(LexEscapeError::Utf8TooLarge));
+            }
+            r = (r << 4) + d;
+        }
+
+        if self.chr != Some(b'}') {
+            return Err(self.escape_error(LexEscapeError::MissingRightBrace));
+        }
+        self.next();
+
+        Ok(r)
     }
 
-    fn scan(&mut self) -> Option<LexToken> {
-        self.buf.clear();
+    // 读取\ddd转义序列，当前字符指向第一个数字
+    //
+    // @return: 转义得到的字节
+    fn read_dec_esc(&mut self) -> Result<u8, LexError> {
+        let mut r: u32 = 0;
+        for _ in 0..3 {
+            match self.chr.and_then(|c| char::from(c).to_digit(10)) {
+                Some(d) => {
+                    r = r * 10 + d;
+                    self.next();
+                }
+                None => break,
+            }
+        }
+
+        if r > u32::from(u8::MAX) {
+            Err(self.escape_error(LexEscapeError::DecimalTooLarge))
+        } else {
+            Ok(r as u8)
+        }
+    }
+
+    // 跳过\z转义序列之后的空白字符(包括换行)，当前字符指向z
+    fn skip_esc_space(&mut self) {
+        self.next();
 
         loop {
-            if is_alpha!(self.chr.unwrap(), IDENT) {
-                if is_alpha!(self.chr.unwrap(), DIGIT) {
-                    return self.number();
-                }
-
-                loop {
-                    self.save_next();
-                    match self.chr {
-                        None => break,
-                        Some(c) => {
-                            if !is_alpha!(c, IDENT) {
-                                break;
-                            }
-                        }
-                    }
+            match self.chr {
+                Some(b'\r') | Some(b'\n') => self.new_line(),
+                c if Self::is_chr(c, SPACE) => {
+                    self.next();
                 }
+                _ => break,
+            }
+        }
+    }
 
-                return self.name();
+    // 读取一个转义序列并将其值追加到value中，当前字符指向反斜杠
+    //
+    // @param value: 字符串的值
+    // @param interp: 是否位于插值字符串中，插值字符串中还可以转义`和{
+    fn read_escape(&mut self, value: &mut Vec<u8>, interp: bool) -> Result<(), LexError> {
+        let c = match self.next() {
+            Some(b'a') => 0x07,
+            Some(b'b') => 0x08,
+            Some(b'f') => 0x0c,
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(b'v') => 0x0b,
+            Some(b'\\') => b'\\',
+            Some(b'"') => b'"',
+            Some(b'\'') => b'\'',
+            Some(c @ (b'`' | b'{')) if interp => c,
+            Some(b'x') if self.dialect.has_extended_escapes() => {
+                value.push(self.read_hex_esc()?);
+                return Ok(());
+            }
+            Some(b'u') if self.dialect.has_utf8_escapes() => {
+                value.extend(utf8_esc(self.read_utf8_esc()?));
+                return Ok(());
+            }
+            Some(b'z') if self.dialect.has_extended_escapes() => {
+                self.skip_esc_space();
+                return Ok(());
+            }
+            Some(b'\n') | Some(b'\r') => {
+                self.new_line();
+                value.push(b'\n');
+                return Ok(());
             }
+            Some(c) if c.is_ascii_digit() => {
+                value.push(self.read_dec_esc()?);
+                return Ok(());
+            }
+            // 字符串未结束，交由调用者处理
+            None => return Ok(()),
+            Some(c) if !self.dialect.has_strict_escapes() => c,
+            _ => return Err(self.escape_error(LexEscapeError::Invalid)),
+        };
+
+        value.push(c);
+        self.next();
+        Ok(())
+    }
+
+    // 接下来的一段源码解析为短字符串，可用的转义序列由方言决定。
+    // 不含转义序列的字符串直接借用源码
+    //
+    // @return: 返回一个Token
+    fn string(&mut self) -> Result<LexToken<'src_lt>, LexError> {
+        let delim = self.chr;
+        let mut value: Option<Vec<u8>> = None;
+        let mut error = None;
+        self.mark();
+        self.next();
 
+        while self.chr != delim {
             match self.chr {
-                Some('\n') | Some('\r') => {
-                    self.new_line();
+                None => {
+                    return Err(
+                        error.unwrap_or_else(|| self.error_eof(LexErrorKind::UnfinishedString))
+                    )
                 }
-                Some(' ') | Some('\t') => {
-                    self.next();
+                Some(b'\n') | Some(b'\r') => {
+                    return Err(error.unwrap_or_else(|| self.error(LexErrorKind::UnfinishedString)))
                 }
-                Some('-') => {
-                    self.next();
-                    if self.chr.ne(&Some('-')) {
-                        return Some(LexToken::Sub);
+                Some(b'\\') => {
+                    let mut v = self.escaped_value(value.take());
+                    let r = self.read_escape(&mut v, false);
+                    self.defer_error(r, &mut error)?;
+                    value = Some(v);
+                }
+                Some(c) => {
+                    if let Some(v) = value.as_mut() {
+                        v.push(c);
                     }
                     self.next();
-                    if self.chr.eq(&Some('[')) {
-                        let sep = self.skip_eq();
-                        self.buf.clear();
-
-                        if sep >= 0 {
-                            self.longstring(sep);
-                            continue;
-                        }
-                    }
+                }
+            }
+        }
+        self.next();
 
-                    while !self.is_eol() && self.chr.ne(&None) {
-                        self.next();
-                    }
+        if let Some(e) = error {
+            return Err(e);
+        }
+        let value = self.take_string(value);
+        Ok(self.str_token(value))
+    }
+
+    // 恢复模式下暂存字符串中的第一个错误并继续扫描到字符串结束，使之后的内容不会被当作代码扫描
+    //
+    // @param r: 扫描结果
+    // @param error: 暂存的错误
+    //
+    // @return: 非恢复模式下直接返回错误
+    fn defer_error(
+        &self,
+        r: Result<(), LexError>,
+        error: &mut Option<LexError>,
+    ) -> Result<(), LexError> {
+        match r {
+            Err(e) if self.recovery => {
+                error.get_or_insert(e);
+                Ok(())
+            }
+            r => r,
+        }
+    }
+
+    // 字符串中出现转义序列时，字符串的值无法再借用源码，需将此前的内容复制出来
+    //
+    // @param value: 已经复制出来的值
+    //
+    // @return: 字符串的值
+    fn escaped_value(&self, value: Option<Vec<u8>>) -> Vec<u8> {
+        match value {
+            Some(v) => v,
+            None => self.lexeme()[1..].to_vec(),
+        }
+    }
+
+    // 取出字符串的值，未复制出来时借用去掉首尾定界符的原始源码
+    //
+    // @param value: 已经复制出来的值
+    //
+    // @return: 字符串的值
+    fn take_string(&mut self, value: Option<Vec<u8>>) -> Cow<'src_lt, [u8]> {
+        let raw = self.take_lexeme();
+        match value {
+            Some(v) => Cow::Owned(v),
+            None => {
+                let end = raw.len() - 1;
+                Self::slice_lexeme(raw, 1, end)
+            }
+        }
+    }
+
+    // 将接下来的一段源码解析为Luau插值字符串的一部分，当前字符指向`或结束插值表达式的}。
+    // 遇到{时开始一个插值表达式，之后的Token照常扫描，直到与之匹配的}
+    //
+    // @param begin: 是否为插值字符串的开头
+    //
+    // @return: 返回一个Token
+    fn interp_string(&mut self, begin: bool) -> Result<LexToken<'src_lt>, LexError> {
+        let mut value: Option<Vec<u8>> = None;
+        let mut error = None;
+        self.mark();
+        self.next();
+
+        loop {
+            match self.chr {
+                None => {
+                    return Err(
+                        error.unwrap_or_else(|| self.error_eof(LexErrorKind::UnfinishedString))
+                    )
                 }
-                Some('[') => {
-                    let sep = self.skip_eq();
-                    if sep.gt(&0) {
-                        return self.longstring(sep);
-                    } else if sep.eq(&-1) {
-                        return Some(LexToken::SquareBracketLeft);
-                    } else {
-                        return None;
-                    }
+                Some(b'\n') | Some(b'\r') => {
+                    return Err(error.unwrap_or_else(|| self.error(LexErrorKind::UnfinishedString)))
                 }
-                Some('=') => {
-                    self.next();
-                    if self.chr.ne(&Some('=')) {
-                        return Some(LexToken::Assign);
-                    } else {
-                        self.next();
-                        return Some(LexToken::Equal);
-                    }
+                Some(b'\\') => {
+                    let mut v = self.escaped_value(value.take());
+                    let r = self.read_escape(&mut v, true);
+                    self.defer_error(r, &mut error)?;
+                    value = Some(v);
                 }
-                Some('<') => {
+                Some(b'`') => {
                     self.next();
-                    if self.chr.ne(&Some('=')) {
-                        return Some(LexToken::Less);
-                    } else {
-                        self.next();
-                        return Some(LexToken::LessEqual);
+                    if let Some(e) = error {
+                        return Err(e);
                     }
-                }
-                Some('>') => {
-                    self.next();
-                    if self.chr.ne(&Some('=')) {
-                        return Some(LexToken::Greate);
+                    let value = self.take_string(value);
+                    return Ok(if begin {
+                        LexToken::InterpSimple(value)
                     } else {
-                        self.next();
-                        return Some(LexToken::GreateEqual);
-                    }
+                        LexToken::InterpEnd(value)
+                    });
                 }
-                Some('~') => {
-                    self.next();
-                    if self.chr.ne(&Some('=')) {
-                        return None;
-                    } else {
+                Some(b'{') => {
+                    if self.next() == Some(b'{') {
                         self.next();
-                        return Some(LexToken::NotEqual);
+                        let e = Err(self.error(LexErrorKind::DoubleBrace));
+                        self.defer_error(e, &mut error)?;
+                        continue;
+                    }
+                    // 插值表达式照常开始，使之后的}能够回到插值字符串中
+                    self.interp.push(0);
+                    if let Some(e) = error {
+                        return Err(e);
                     }
+                    let value = self.take_string(value);
+                    return Ok(if begin {
+                        LexToken::InterpBegin(value)
+                    } else {
+                        LexToken::InterpMid(value)
+                    });
                 }
-                Some(':') => {
+                Some(c) => {
+                    if let Some(v) = value.as_mut() {
+                        v.push(c);
+                    }
                     self.next();
-                    if self.chr.ne(&Some(':')) {
-                        return Some(LexToken::MethodCall);
-                    } else {
+                }
+            }
+        }
+    }
+
+    // 设置词法分析所遵循的语言方言
+    //
+    // @param dialect: 语言方言
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    // 设置是否开启恢复模式：开启后遇到词法错误不再中止扫描，而是返回携带该错误的Error Token，
+    // 并从出错的字符串、数字或符号之后继续扫描，从而一次报告源码中的全部词法错误。
+    // 读取源码失败时仍然直接返回错误
+    //
+    // @param recovery: 是否开启
+    pub fn set_recovery(&mut self, recovery: bool) {
+        self.recovery = recovery;
+    }
+
+    // 设置setup时是否与luaL_loadfilex一样跳过开头的UTF-8 BOM及以'#'开头的第一行，
+    // 用于扫描从文件中载入的源码；默认不跳过，与load一致
+    //
+    // @param skip_header: 是否跳过
+    pub fn set_skip_header(&mut self, skip_header: bool) {
+        self.skip_header = skip_header;
+    }
+
+    // 获取词法分析所遵循的语言方言
+    //
+    // @return: 语言方言
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    // 替换字符串驻留表，用于在多个LexStatus及之后的编译过程间共享同一张表
+    //
+    // @param interner: 字符串驻留表
+    //
+    // @return: 原先的字符串驻留表
+    pub fn set_interner(&mut self, interner: Interner) -> Interner {
+        std::mem::replace(&mut self.interner, interner)
+    }
+
+    // 获取字符串驻留表
+    //
+    // @return: 字符串驻留表
+    pub fn interner(&self) -> &Interner {
+        &self.interner
+    }
+
+    // 获取可修改的字符串驻留表
+    //
+    // @return: 字符串驻留表
+    pub fn interner_mut(&mut self) -> &mut Interner {
+        &mut self.interner
+    }
+
+    // 取出字符串驻留表
+    //
+    // @return: 字符串驻留表
+    pub fn into_interner(self) -> Interner {
+        self.interner
+    }
+
+    // 将字符串常量转化为Token，短字符串驻留为Symbol
+    //
+    // @param value: 字符串的值
+    //
+    // @return: Token
+    fn str_token(&mut self, value: Cow<'src_lt, [u8]>) -> LexToken<'src_lt> {
+        if value.len() <= MAX_SHORT_LEN {
+            LexToken::ShortStr(self.interner.intern(&*value))
+        } else {
+            LexToken::Str(value)
+        }
+    }
+
+    // 获取当前所在的行号
+    //
+    // @return: 行号
+    pub fn line_number(&self) -> u32 {
+        self.line_number
+    }
+
+    // 获取当前所在的列号
+    //
+    // @return: 列号
+    pub fn column(&self) -> u32 {
+        self.column
+    }
+
+    // 获取当前字符在源码中的位置
+    //
+    // @return: 位置
+    pub fn position(&self) -> Position {
+        Position::new(self.offset, self.line_number, self.column)
+    }
+
+    // 获取最近一次扫描得到的Token在源码中的区间
+    //
+    // @return: 区间
+    pub fn span(&self) -> Span {
+        Span::new(self.token_start, self.position())
+    }
+
+    // 读取第一个字符。开启set_skip_header时还会跳过开头的UTF-8 BOM及以'#'开头的第一行，
+    // 第一行末尾的换行符会被保留，因此之后的行号不受影响
+    pub fn setup(&mut self) {
+        self.next();
+        if !self.skip_header {
+            return;
+        }
+
+        let lossless = self.lossless;
+        self.lossless = true;
+
+        self.token_start = self.position();
+        // 与Lua 5.4的skipBOM相同，只跳过完整的BOM，不完整时0xEF作为普通字符保留
+        if self.chr.eq(&Some(0xef)) && self.peek_bytes(2) == b"\xbb\xbf" {
+            self.next();
+            self.next();
+            self.next();
+            self.column = 1;
+            let trivia = self.take_trivia(TriviaKind::Bom);
+            self.header.push(trivia);
+        }
+
+        self.token_start = self.position();
+        if self.chr.eq(&Some(b'#')) {
+            while !self.is_eol() && self.chr.ne(&None) {
+                self.next();
+            }
+            let trivia = self.take_trivia(TriviaKind::Shebang);
+            self.header.push(trivia);
+        }
+
+        self.lossless = lossless;
+    }
+
+    // 从源码中间的某个位置开始扫描，读取第一个字符但不跳过BOM及shebang；
+    // 传入的源码应从该位置开始，之后得到的区间均以该位置为基准
+    //
+    // @param position: 源码开头在完整源码中的位置
+    pub fn setup_at(&mut self, position: Position) {
+        self.offset = position.offset;
+        self.line_number = position.line;
+        self.column = position.column;
+        self.next();
+    }
+
+    // 将名字的原始源码转化为关键字或驻留为Name
+    //
+    // @param tok: 名字的原始源码
+    //
+    // @return: 返回一个Token
+    fn name(&mut self, tok: Cow<'src_lt, [u8]>) -> Result<LexToken<'src_lt>, LexError> {
+        Ok(match &*tok {
+            b"and" => LexToken::And,
+            b"break" => LexToken::Break,
+            b"do" => LexToken::Do,
+            b"else" => LexToken::Else,
+            b"elseif" => LexToken::ElseIf,
+            b"end" => LexToken::End,
+            b"false" => LexToken::False,
+            b"for" => LexToken::For,
+            b"function" => LexToken::Function,
+            b"goto" if self.dialect.has_goto() => LexToken::Goto,
+            b"if" => LexToken::If,
+            b"in" => LexToken::In,
+            b"local" => LexToken::Local,
+            b"nil" => LexToken::Nil,
+            b"not" => LexToken::Not,
+            b"or" => LexToken::Or,
+            b"repeat" => LexToken::Repeat,
+            b"return" => LexToken::Return,
+            b"then" => LexToken::Then,
+            b"true" => LexToken::True,
+            b"until" => LexToken::Until,
+            b"while" => LexToken::While,
+            _ => LexToken::Name(self.interner.intern(&*tok)),
+        })
+    }
+
+    // 若方言支持复合赋值且当前字符为等号，则返回复合赋值运算符
+    //
+    // @param op: 运算符
+    // @param assign: 对应的复合赋值运算符
+    //
+    // @return: Token
+    fn compound(&mut self, op: LexToken<'src_lt>, assign: LexToken<'src_lt>) -> LexToken<'src_lt> {
+        if self.chr.eq(&Some(b'=')) && self.dialect.has_compound_assignment() {
+            self.next();
+            assign
+        } else {
+            op
+        }
+    }
+
+    // 扫描下一个Token或一段空白、注释
+    //
+    // @return: Token或空白、注释的类型
+    fn scan_item(&mut self) -> Result<ScanItem<'src_lt>, LexError> {
+        match self.read_item() {
+            Err(e) if self.recovery && !matches!(e.kind, LexErrorKind::ReadFailed(_)) => {
+                Ok(ScanItem::Token(LexToken::Error(Box::new(e))))
+            }
+            item => item,
+        }
+    }
+
+    // 扫描下一个Token或一段空白、注释，遇到错误时直接返回
+    //
+    // @return: Token或空白、注释
+    fn read_item(&mut self) -> Result<ScanItem<'src_lt>, LexError> {
+        self.buf.clear();
+        self.mark = None;
+        self.raw.clear();
+        self.token_start = self.position();
+
+        let c = match self.chr {
+            Some(c) => c,
+            None if self.read_error.is_some() => {
+                return Err(self.error_eof(LexErrorKind::UnexpectedSymbol))
+            }
+            None => return Ok(ScanItem::Token(LexToken::Eof)),
+        };
+
+        // 按首字符的类别分派，只有标点需要再按具体的字符区分
+        match CHAR_CLASSES[c as usize] {
+            CharClass::Digit => self.number().map(ScanItem::Token),
+            CharClass::Name => {
+                self.mark();
+                while Self::is_chr(self.next(), IDENT) {}
+
+                let tok = self.take_lexeme();
+                self.name(tok).map(ScanItem::Token)
+            }
+            CharClass::Newline => {
+                self.new_line();
+                Ok(ScanItem::Trivia(TriviaKind::Newline))
+            }
+            CharClass::Space => {
+                while Self::class(self.next()) == Some(CharClass::Space) {}
+                Ok(ScanItem::Trivia(TriviaKind::Whitespace))
+            }
+            CharClass::Punct => self.read_punct(c),
+            CharClass::Other => Err(self.unexpected_symbol(c)),
+        }
+    }
+
+    // 扫描以标点开始的Token或注释
+    //
+    // @param c: 当前字符
+    //
+    // @return: Token或注释的类型
+    fn read_punct(&mut self, c: u8) -> Result<ScanItem<'src_lt>, LexError> {
+        match c {
+            b'-' => {
+                self.next();
+                if self.chr.eq(&Some(b'=')) && self.dialect.has_compound_assignment() {
+                    self.next();
+                    return Ok(ScanItem::Token(LexToken::SubAssign));
+                }
+                if self.chr.ne(&Some(b'-')) {
+                    return Ok(ScanItem::Token(LexToken::Sub));
+                }
+                self.next();
+                if self.chr.eq(&Some(b'[')) {
+                    let sep = self.skip_eq();
+                    self.buf.clear();
+
+                    if sep >= 0 {
+                        self.longstring(sep, true)?;
+                        return Ok(ScanItem::Trivia(TriviaKind::LongComment));
+                    }
+                }
+
+                while !self.is_eol() && self.chr.ne(&None) {
+                    self.next();
+                }
+                Ok(ScanItem::Trivia(TriviaKind::LineComment))
+            }
+            b'[' => {
+                self.mark();
+                let sep = self.skip_eq();
+                if sep >= 0 {
+                    let value = self.longstring(sep, false)?;
+                    Ok(ScanItem::Token(self.str_token(value)))
+                } else if sep.eq(&-1) {
+                    self.mark = None;
+                    Ok(ScanItem::Token(LexToken::SquareBracketLeft))
+                } else {
+                    Err(self.error(LexErrorKind::InvalidLongStringDelimiter))
+                }
+            }
+            b'=' => {
+                self.next();
+                if self.chr.ne(&Some(b'=')) {
+                    Ok(ScanItem::Token(LexToken::Assign))
+                } else {
+                    self.next();
+                    Ok(ScanItem::Token(LexToken::Equal))
+                }
+            }
+            b'<' => {
+                self.next();
+                match self.chr {
+                    Some(b'=') => {
                         self.next();
-                        return Some(LexToken::Label);
+                        Ok(ScanItem::Token(LexToken::LessEqual))
                     }
+                    Some(b'<') if self.dialect.has_bitwise_operators() => {
+                        self.next();
+                        Ok(ScanItem::Token(LexToken::ShiftLeft))
+                    }
+                    _ => Ok(ScanItem::Token(LexToken::Less)),
+                }
+            }
+            b'>' => {
+                self.next();
+                match self.chr {
+                    Some(b'=') => {
+                        self.next();
+                        Ok(ScanItem::Token(LexToken::GreateEqual))
+                    }
+                    Some(b'>') if self.dialect.has_bitwise_operators() => {
+                        self.next();
+                        Ok(ScanItem::Token(LexToken::ShiftRight))
+                    }
+                    _ => Ok(ScanItem::Token(LexToken::Greate)),
+                }
+            }
+            b'~' => {
+                self.next();
+                if self.chr.eq(&Some(b'=')) {
+                    self.next();
+                    Ok(ScanItem::Token(LexToken::NotEqual))
+                } else if self.dialect.has_bitwise_operators() {
+                    Ok(ScanItem::Token(LexToken::BitXor))
+                } else {
+                    self.save(b'~');
+                    Err(self.error(LexErrorKind::UnexpectedSymbol))
+                }
+            }
+            b'&' if self.dialect.has_bitwise_operators() => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::BitAnd))
+            }
+            b'|' if self.dialect.has_bitwise_operators() => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::BitOr))
+            }
+            b':' => {
+                self.next();
+                if self.chr.ne(&Some(b':')) || !self.dialect.has_goto() {
+                    Ok(ScanItem::Token(LexToken::MethodCall))
+                } else {
+                    self.next();
+                    Ok(ScanItem::Token(LexToken::Label))
                 }
-                Some('\'') | Some('"') => {
-                    return self.string();
+            }
+            b'\'' | b'"' => self.string().map(ScanItem::Token),
+            b'`' if self.dialect.has_interpolated_strings() => {
+                self.interp_string(true).map(ScanItem::Token)
+            }
+            b'.' => {
+                if Self::is_chr(self.peek_next(), DIGIT) {
+                    return self.number().map(ScanItem::Token);
                 }
-                Some('.') => {
-                    if self.save_next().eq(&Some('.')) {
+                if self.next().eq(&Some(b'.')) {
+                    self.next();
+                    if self.chr.eq(&Some(b'.')) {
                         self.next();
-                        if self.chr.eq(&Some('.')) {
-                            self.next();
-                            return Some(LexToken::Dots);
-                        }
-                        return Some(LexToken::Concat);
-                    } else if is_alpha!(self.chr.unwrap(), DIGIT) {
-                        return Some(LexToken::Dot);
-                    } else {
-                        return self.number();
+                        return Ok(ScanItem::Token(LexToken::Dots));
+                    }
+                    Ok(ScanItem::Token(
+                        self.compound(LexToken::Concat, LexToken::ConcatAssign),
+                    ))
+                } else {
+                    Ok(ScanItem::Token(LexToken::Dot))
+                }
+            }
+            b'+' => {
+                self.next();
+                Ok(ScanItem::Token(
+                    self.compound(LexToken::Add, LexToken::AddAssign),
+                ))
+            }
+            b'*' => {
+                self.next();
+                Ok(ScanItem::Token(
+                    self.compound(LexToken::Mul, LexToken::MulAssign),
+                ))
+            }
+            b'/' => {
+                self.next();
+                if self.chr.ne(&Some(b'/')) || !self.dialect.has_floor_division() {
+                    Ok(ScanItem::Token(
+                        self.compound(LexToken::Div, LexToken::DivAssign),
+                    ))
+                } else {
+                    self.next();
+                    Ok(ScanItem::Token(
+                        self.compound(LexToken::FloorDiv, LexToken::FloorDivAssign),
+                    ))
+                }
+            }
+            b'%' => {
+                self.next();
+                Ok(ScanItem::Token(
+                    self.compound(LexToken::Mod, LexToken::ModAssign),
+                ))
+            }
+            b'^' => {
+                self.next();
+                Ok(ScanItem::Token(
+                    self.compound(LexToken::Pow, LexToken::PowAssign),
+                ))
+            }
+            b'#' => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::Len))
+            }
+            b']' => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::SquareBracketRight))
+            }
+            b'(' => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::RoundBracketLeft))
+            }
+            b')' => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::RoundBracketRight))
+            }
+            b'{' => {
+                self.next();
+                if let Some(depth) = self.interp.last_mut() {
+                    *depth += 1;
+                }
+                Ok(ScanItem::Token(LexToken::CurlyBracketLeft))
+            }
+            b'}' => {
+                match self.interp.last_mut() {
+                    // 插值表达式结束，继续扫描插值字符串
+                    Some(0) => {
+                        self.interp.pop();
+                        return self.interp_string(false).map(ScanItem::Token);
                     }
+                    Some(depth) => *depth -= 1,
+                    None => {}
                 }
-                Some('+') => return Some(LexToken::Add),
-                Some('*') => return Some(LexToken::Mul),
-                Some('/') => return Some(LexToken::Div),
-                Some('%') => return Some(LexToken::Mod),
-                None => return Some(LexToken::Eof),
-                _ => return None,
+                self.next();
+                Ok(ScanItem::Token(LexToken::CurlyBracketRight))
+            }
+            b';' => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::Semicolon))
+            }
+            b',' => {
+                self.next();
+                Ok(ScanItem::Token(LexToken::Comma))
+            }
+            _ => Err(self.unexpected_symbol(c)),
+        }
+    }
+
+    // 构造无法识别的字符的词法错误，并跳过该字符
+    //
+    // @param c: 当前字符
+    //
+    // @return: 词法错误
+    fn unexpected_symbol(&mut self, c: u8) -> LexError {
+        self.save(c);
+        let e = self.error(LexErrorKind::UnexpectedSymbol);
+        self.next();
+        e
+    }
+
+    // 扫描下一个Token，跳过其间的空白及注释
+    //
+    // @return: Token
+    pub fn scan(&mut self) -> Result<LexToken<'src_lt>, LexError> {
+        loop {
+            if let ScanItem::Token(token) = self.scan_item()? {
+                return Ok(token);
             }
         }
     }
+
+    // 判断当前字符是否为一段空白或注释的开始
+    //
+    // @return: 是否为空白或注释
+    fn at_trivia(&mut self) -> bool {
+        match Self::class(self.chr) {
+            Some(CharClass::Space) | Some(CharClass::Newline) => true,
+            _ => self.chr == Some(b'-') && self.peek_next() == Some(b'-'),
+        }
+    }
+
+    // 将最近一次扫描得到的空白或注释连同其原始内容取出
+    //
+    // @param kind: 空白或注释的类型
+    //
+    // @return: Trivia
+    fn take_trivia(&mut self, kind: TriviaKind) -> Trivia {
+        Trivia::new(kind, std::mem::take(&mut self.raw), self.span())
+    }
+
+    // 以无损模式扫描下一个Token，保留其原始内容及前后的空白、注释。
+    // 首次调用时开启无损模式，此前不应调用其他扫描方法，否则已扫描的源码不会被保留
+    //
+    // @return: 保留原始内容的Token
+    pub fn scan_lossless(&mut self) -> Result<LosslessToken<'src_lt>, LexError> {
+        self.lossless = true;
+
+        let mut leading = std::mem::take(&mut self.header);
+        let token = loop {
+            match self.scan_item()? {
+                ScanItem::Trivia(kind) => leading.push(self.take_trivia(kind)),
+                ScanItem::Token(token) => break token,
+            }
+        };
+        let token = SpannedToken::new(token, self.span());
+        let text = std::mem::take(&mut self.raw);
+
+        // 同一行内Token之后的空白及注释，连同行尾的换行一起归属于该Token
+        let mut trailing = Vec::new();
+        while token.token != LexToken::Eof && self.at_trivia() {
+            if let ScanItem::Trivia(kind) = self.scan_item()? {
+                trailing.push(self.take_trivia(kind));
+                if kind == TriviaKind::Newline {
+                    break;
+                }
+            }
+        }
+
+        Ok(LosslessToken {
+            leading,
+            token,
+            text,
+            trailing,
+        })
+    }
+
+    // 扫描下一个Token，并附带其在源码中的区间
+    //
+    // @return: 携带区间的Token
+    pub fn scan_spanned(&mut self) -> Result<SpannedToken<'src_lt>, LexError> {
+        let token = self.scan()?;
+        Ok(SpannedToken::new(token, self.span()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::lex::error::{LexError, LexErrorKind, LexEscapeError};
 
     #[test]
     fn lex_scan_dec_number() {
@@ -411,8 +1454,8 @@ mod tests {
         lex.setup();
 
         let token = lex.scan();
-        assert!(token.is_some());
-        if let Some(LexToken::Number(x)) = token {
+        assert!(token.is_ok());
+        if let Ok(LexToken::Number(x)) = token {
             assert_eq!(x.as_int(), 1123);
         }
     }
@@ -423,8 +1466,8 @@ mod tests {
         lex.setup();
 
         let token = lex.scan();
-        assert!(token.is_some());
-        if let Some(LexToken::Number(x)) = token {
+        assert!(token.is_ok());
+        if let Ok(LexToken::Number(x)) = token {
             assert_eq!(x.as_int(), 0x1123);
         }
     }
@@ -432,13 +1475,20 @@ mod tests {
     #[test]
     fn lex_scan_bin_number() {
         let mut lex = LexStatus::new("0b0001000100100011");
+        lex.set_dialect(Dialect::LuaJit);
         lex.setup();
 
         let token = lex.scan();
-        assert!(token.is_some());
-        if let Some(LexToken::Number(x)) = token {
-            assert_eq!(x.as_int(), 0x1123);
+        assert!(token.is_ok());
+        if let Ok(LexToken::Number(x)) = token {
+            assert_eq!(x.as_float(), 4387.0);
         }
+
+        // 二进制整数只在LuaJIT中可用
+        assert_eq!(
+            scan_dialect_err("0b101", Dialect::Lua54).kind,
+            LexErrorKind::MalformedNumber
+        );
     }
 
     #[test]
@@ -447,8 +1497,8 @@ mod tests {
         lex.setup();
 
         let token = lex.scan();
-        assert!(token.is_some());
-        if let Some(LexToken::Number(x)) = token {
+        assert!(token.is_ok());
+        if let Ok(LexToken::Number(x)) = token {
             assert_eq!(x.as_float(), 0.51f64);
         }
     }
@@ -459,19 +1509,69 @@ mod tests {
         lex.setup();
 
         let token = lex.scan();
-        assert!(token.is_some());
-        if let Some(LexToken::Number(x)) = token {
+        assert!(token.is_ok());
+        if let Ok(LexToken::Number(x)) = token {
             assert_eq!(x.as_float(), 1123.51f64);
         }
     }
 
-    fn assert_name(s: &str) -> LexToken {
+    #[test]
+    fn lex_scan_int_overflow() {
+        let mut lex = LexStatus::new("9223372036854775808 0xffffffffffffffff");
+        lex.setup();
+
+        match lex.scan() {
+            Ok(LexToken::Number(x)) => {
+                assert!(!x.is_int());
+                assert_eq!(x.as_float(), 9223372036854775808f64);
+            }
+            _ => panic!("number expected"),
+        }
+        match lex.scan() {
+            Ok(LexToken::Number(x)) => {
+                assert!(x.is_int());
+                assert_eq!(x.as_int(), -1);
+            }
+            _ => panic!("number expected"),
+        }
+    }
+
+    #[test]
+    fn lex_scan_luajit_suffix() {
+        let mut lex = LexStatus::new("123LL 0xffULL 2.5i");
+        lex.set_dialect(Dialect::LuaJit);
+        lex.setup();
+
+        assert!(lex.scan() == Ok(LexToken::Number(LexNumberValue::Int64(123))));
+        assert!(lex.scan() == Ok(LexToken::Number(LexNumberValue::UInt64(0xff))));
+        assert!(lex.scan() == Ok(LexToken::Number(LexNumberValue::Imaginary(2.5))));
+        assert!(lex.scan() == Ok(LexToken::Eof));
+
+        let mut lex = LexStatus::new("123LL");
+        lex.setup();
+        assert_eq!(scan_err(&mut lex).kind, LexErrorKind::MalformedNumber);
+    }
+
+    #[test]
+    fn lex_scan_float_exponent() {
+        let mut lex = LexStatus::new("1e-3 0x1p-2 3E+2");
+        lex.setup();
+
+        for expect in [1e-3, 0.25, 300f64] {
+            match lex.scan() {
+                Ok(LexToken::Number(x)) => assert_eq!(x.as_float(), expect),
+                _ => panic!("number expected"),
+            }
+        }
+    }
+
+    fn assert_name(s: &str) -> LexToken<'_> {
         let mut lex = LexStatus::new(s);
         lex.setup();
         let token = lex.scan();
 
-        assert!(token.is_some());
-        if let Some(x) = token {
+        assert!(token.is_ok());
+        if let Ok(x) = token {
             x
         } else {
             LexToken::Eof
@@ -500,7 +1600,7 @@ mod tests {
         assert!(assert_name("return") == LexToken::Return);
         assert!(assert_name("then") == LexToken::Then);
         assert!(assert_name("true") == LexToken::True);
-        assert!(assert_name("util") == LexToken::Util);
+        assert!(assert_name("until") == LexToken::Until);
         assert!(assert_name("while") == LexToken::While);
     }
 
@@ -510,6 +1610,958 @@ mod tests {
         assert!(assert_name("-") == LexToken::Sub);
         assert!(assert_name("*") == LexToken::Mul);
         assert!(assert_name("/") == LexToken::Div);
-        assert!(assert_name("%") == LexToken::Mod)
+        assert!(assert_name("%") == LexToken::Mod);
+        assert!(assert_name("^") == LexToken::Pow);
+        assert!(assert_name("#") == LexToken::Len);
+    }
+
+    #[test]
+    fn lex_scan_bitwise_operator() {
+        assert!(assert_name("&") == LexToken::BitAnd);
+        assert!(assert_name("|") == LexToken::BitOr);
+        assert!(assert_name("~") == LexToken::BitXor);
+        assert!(assert_name("~=") == LexToken::NotEqual);
+        assert!(assert_name("<<") == LexToken::ShiftLeft);
+        assert!(assert_name(">>") == LexToken::ShiftRight);
+        assert!(assert_name("//") == LexToken::FloorDiv);
+        assert!(assert_name("<=") == LexToken::LessEqual);
+        assert!(assert_name(">=") == LexToken::GreateEqual);
+
+        let mut lex = LexStatus::new("~a&b|c>>1<<2//3~~d");
+        lex.setup();
+        for expect in [
+            LexToken::BitXor,
+            name_token(&mut lex, "a"),
+            LexToken::BitAnd,
+            name_token(&mut lex, "b"),
+            LexToken::BitOr,
+            name_token(&mut lex, "c"),
+            LexToken::ShiftRight,
+        ] {
+            assert!(lex.scan() == Ok(expect));
+        }
+        assert!(matches!(lex.scan(), Ok(LexToken::Number(_))));
+        assert!(lex.scan() == Ok(LexToken::ShiftLeft));
+        assert!(matches!(lex.scan(), Ok(LexToken::Number(_))));
+        assert!(lex.scan() == Ok(LexToken::FloorDiv));
+        assert!(matches!(lex.scan(), Ok(LexToken::Number(_))));
+        assert!(lex.scan() == Ok(LexToken::BitXor));
+        assert!(lex.scan() == Ok(LexToken::BitXor));
+        assert!(lex.scan() == Ok(name_token(&mut lex, "d")));
+        assert!(lex.scan() == Ok(LexToken::Eof));
+    }
+
+    #[test]
+    fn lex_scan_punctuation() {
+        assert!(assert_name("[") == LexToken::SquareBracketLeft);
+        assert!(assert_name("]") == LexToken::SquareBracketRight);
+        assert!(assert_name("(") == LexToken::RoundBracketLeft);
+        assert!(assert_name(")") == LexToken::RoundBracketRight);
+        assert!(assert_name("{") == LexToken::CurlyBracketLeft);
+        assert!(assert_name("}") == LexToken::CurlyBracketRight);
+        assert!(assert_name(";") == LexToken::Semicolon);
+        assert!(assert_name(",") == LexToken::Comma);
+        assert!(assert_name(":") == LexToken::MethodCall);
+        assert!(assert_name("::") == LexToken::Label);
+        assert!(assert_name(".") == LexToken::Dot);
+        assert!(assert_name("..") == LexToken::Concat);
+        assert!(assert_name("...") == LexToken::Dots);
+
+        let mut lex = LexStatus::new("t[#a]=f(x,{y;z})^2");
+        lex.setup();
+        for expect in [
+            name_token(&mut lex, "t"),
+            LexToken::SquareBracketLeft,
+            LexToken::Len,
+            name_token(&mut lex, "a"),
+            LexToken::SquareBracketRight,
+            LexToken::Assign,
+            name_token(&mut lex, "f"),
+            LexToken::RoundBracketLeft,
+            name_token(&mut lex, "x"),
+            LexToken::Comma,
+            LexToken::CurlyBracketLeft,
+            name_token(&mut lex, "y"),
+            LexToken::Semicolon,
+            name_token(&mut lex, "z"),
+            LexToken::CurlyBracketRight,
+            LexToken::RoundBracketRight,
+            LexToken::Pow,
+        ] {
+            assert!(lex.scan() == Ok(expect));
+        }
+        assert!(matches!(lex.scan(), Ok(LexToken::Number(_))));
+        assert!(lex.scan() == Ok(LexToken::Eof));
+    }
+
+    fn name_token<'a>(lex: &mut LexStatus, s: &str) -> LexToken<'a> {
+        LexToken::Name(lex.interner_mut().intern(s))
+    }
+
+    fn short_str<'a>(lex: &mut LexStatus, s: &[u8]) -> LexToken<'a> {
+        LexToken::ShortStr(lex.interner_mut().intern(s))
+    }
+
+    fn str_value(lex: &LexStatus, token: Result<LexToken, LexError>) -> Vec<u8> {
+        match token {
+            Ok(LexToken::Str(x)) => x.into_owned(),
+            Ok(LexToken::ShortStr(x)) => lex.interner().resolve(x).to_vec(),
+            _ => panic!("string expected"),
+        }
+    }
+
+    fn assert_str(s: &str) -> Vec<u8> {
+        let mut lex = LexStatus::new(s);
+        lex.setup();
+
+        let token = lex.scan();
+        str_value(&lex, token)
+    }
+
+    fn assert_str_invalid(s: &str) {
+        let mut lex = LexStatus::new(s);
+        lex.setup();
+
+        assert!(lex.scan().is_err());
+    }
+
+    #[test]
+    fn lex_scan_string() {
+        assert_eq!(assert_str("'hello'"), b"hello");
+        assert_eq!(assert_str("\"it's\""), b"it's");
+        assert_eq!(assert_str("'中文'"), "中文".as_bytes());
+        assert_eq!(assert_str("[[hello]]"), b"hello");
+        assert_eq!(assert_str("[==[\na]]\n]=]b]==]"), b"a]]\n]=]b");
+    }
+
+    #[test]
+    fn lex_scan_string_escape() {
+        assert_eq!(
+            assert_str(r#"'\a\b\f\n\r\t\v\\\"\''"#),
+            b"\x07\x08\x0c\n\r\t\x0b\\\"'"
+        );
+        assert_eq!(assert_str(r"'\x41\x6a\xfF'"), b"Aj\xff");
+        assert_eq!(assert_str(r"'\65\0066\255\0'"), b"A\x066\xff\x00");
+        assert_eq!(assert_str(r"'\u{48}\u{4e2d}'"), "H中".as_bytes());
+        assert_eq!(assert_str(r"'\u{7FFFFFFF}'"), b"\xfd\xbf\xbf\xbf\xbf\xbf");
+        assert_eq!(assert_str("'a\\z  \n\r\t  b'"), b"ab");
+        assert_eq!(assert_str("'a\\\nb\\\r\nc'"), b"a\nb\nc");
+    }
+
+    #[test]
+    fn lex_scan_string_invalid() {
+        assert_str_invalid("'abc");
+        assert_str_invalid("'abc\n'");
+        assert_str_invalid(r"'\q'");
+        assert_str_invalid(r"'\x4'");
+        assert_str_invalid(r"'\xg0'");
+        assert_str_invalid(r"'\256'");
+        assert_str_invalid(r"'\u48'");
+        assert_str_invalid(r"'\u{48'");
+        assert_str_invalid(r"'\u{}'");
+        assert_str_invalid(r"'\u{80000000}'");
+        assert_str_invalid("[[abc");
+    }
+
+    fn assert_error(s: &str) -> LexError {
+        let mut lex = LexStatus::new(s);
+        lex.setup();
+
+        loop {
+            match lex.scan() {
+                Ok(LexToken::Eof) => panic!("{} has no lexical error", s),
+                Ok(_) => continue,
+                Err(e) => return e,
+            }
+        }
+    }
+
+    #[test]
+    fn lex_scan_error() {
+        assert_eq!(
+            assert_error("x = 'abc").to_string(),
+            "1:9: unfinished string near '<eof>'"
+        );
+        assert_eq!(
+            assert_error("x = 'abc\ny'").to_string(),
+            "1:9: unfinished string near ''abc'"
+        );
+        assert_eq!(
+            assert_error("\n\nx = [==[abc\n").to_string(),
+            "4:1: unfinished long string (starting at line 3) near '<eof>'"
+        );
+        assert_eq!(
+            assert_error("--[[abc").kind,
+            LexErrorKind::UnfinishedLongComment(1)
+        );
+        assert_eq!(
+            assert_error("x = [=abc").to_string(),
+            "1:7: invalid long string delimiter near '[='"
+        );
+        assert_eq!(
+            assert_error("x = 3.4.5 + 1").to_string(),
+            "1:10: malformed number near '3.4.5'"
+        );
+        assert_eq!(assert_error("x = 0xg").kind, LexErrorKind::MalformedNumber);
+        assert_eq!(
+            assert_error(r"'\q'").to_string(),
+            r"1:4: invalid escape sequence near ''\q'"
+        );
+        assert_eq!(
+            assert_error(r"'\xz1'").to_string(),
+            r"1:5: hexadecimal digit expected near ''\xz'"
+        );
+        assert_eq!(
+            assert_error(r"'\u{110000000}'").kind,
+            LexErrorKind::InvalidEscape(LexEscapeError::Utf8TooLarge)
+        );
+        assert_eq!(
+            assert_error(r"'\300'").kind,
+            LexErrorKind::InvalidEscape(LexEscapeError::DecimalTooLarge)
+        );
+        assert_eq!(
+            assert_error("x = @").to_string(),
+            "1:5: unexpected symbol near '@'"
+        );
+    }
+
+    #[test]
+    fn lex_scan_eof() {
+        let mut lex = LexStatus::new("  -- comment\n--[==[ long\ncomment ]==]  ");
+        lex.setup();
+
+        assert!(lex.scan() == Ok(LexToken::Eof));
+        assert_eq!(lex.line_number(), 3);
+    }
+
+    fn assert_spans(s: &str) -> Vec<(usize, usize, u32, u32, u32, u32)> {
+        let mut lex = LexStatus::new(s);
+        lex.setup();
+
+        let mut ret = Vec::new();
+        loop {
+            let t = lex.scan_spanned().unwrap();
+            let span = t.span;
+            ret.push((
+                span.start.offset,
+                span.end.offset,
+                span.start.line,
+                span.start.column,
+                span.end.line,
+                span.end.column,
+            ));
+            if t.token == LexToken::Eof {
+                return ret;
+            }
+        }
+    }
+
+    #[test]
+    fn lex_scan_span() {
+        assert_eq!(
+            assert_spans("local x = 12"),
+            vec![
+                (0, 5, 1, 1, 1, 6),
+                (6, 7, 1, 7, 1, 8),
+                (8, 9, 1, 9, 1, 10),
+                (10, 12, 1, 11, 1, 13),
+                (12, 12, 1, 13, 1, 13),
+            ]
+        );
+        assert_eq!(
+            assert_spans("a\r\nb\n\rc\n\nd"),
+            vec![
+                (0, 1, 1, 1, 1, 2),
+                (3, 4, 2, 1, 2, 2),
+                (6, 7, 3, 1, 3, 2),
+                (9, 10, 5, 1, 5, 2),
+                (10, 10, 5, 2, 5, 2),
+            ]
+        );
+        assert_eq!(
+            assert_spans("'中文' [[a\r\nb]]"),
+            vec![
+                (0, 8, 1, 1, 1, 9),
+                (9, 17, 1, 10, 2, 4),
+                (17, 17, 2, 4, 2, 4),
+            ]
+        );
+    }
+
+    fn scan_all(mut lex: LexStatus) -> Vec<LexToken> {
+        lex.setup();
+
+        let mut ret = Vec::new();
+        loop {
+            let token = lex.scan().unwrap();
+            if token == LexToken::Eof {
+                return ret;
+            }
+            ret.push(token);
+        }
+    }
+
+    const CHUNKED_SRC: &str =
+        "local s = '中文\\u{4e2d}' -- 注释\nreturn [==[\n长字符串]==] .. 0x10";
+
+    #[test]
+    fn lex_scan_chunks() {
+        let expect = scan_all(LexStatus::new(CHUNKED_SRC));
+        assert_eq!(expect.len(), 8);
+
+        // 在每个字节处切分源码，包括多字节字符的中间
+        let bytes = CHUNKED_SRC.as_bytes();
+        for i in 0..bytes.len() {
+            let lex = LexStatus::from_chunks(vec![&bytes[..i], &bytes[i..]]);
+            assert!(scan_all(lex) == expect);
+        }
+
+        let lex = LexStatus::from_chunks(bytes.chunks(1).map(|c| c.to_vec()));
+        assert!(scan_all(lex) == expect);
+    }
+
+    #[test]
+    fn lex_scan_fn() {
+        let mut lines = CHUNKED_SRC.lines().map(|l| format!("{}\n", l).into_bytes());
+        let lex = LexStatus::from_fn(move || lines.next());
+
+        assert!(scan_all(lex) == scan_all(LexStatus::new(CHUNKED_SRC)));
+
+        // 空的源码段表示源码结束
+        let mut chunks = vec![b"b".to_vec(), Vec::new(), b"a ".to_vec()];
+        let mut lex = LexStatus::from_fn(move || chunks.pop());
+        let a = name_token(&mut lex, "a");
+        assert!(scan_all(lex) == vec![a]);
+    }
+
+    struct ByteReader<'a> {
+        src: &'a [u8],
+        fail: bool,
+    }
+
+    impl<'a> Read for ByteReader<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.src.split_first() {
+                Some((b, rest)) => {
+                    buf[0] = *b;
+                    self.src = rest;
+                    Ok(1)
+                }
+                None if self.fail => Err(io::Error::from(io::ErrorKind::PermissionDenied)),
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn lex_scan_read() {
+        let lex = LexStatus::from_read(ByteReader {
+            src: CHUNKED_SRC.as_bytes(),
+            fail: false,
+        });
+        assert!(scan_all(lex) == scan_all(LexStatus::new(CHUNKED_SRC)));
+
+        let lex = LexStatus::from_buf_read(io::BufReader::with_capacity(2, CHUNKED_SRC.as_bytes()));
+        assert!(scan_all(lex) == scan_all(LexStatus::new(CHUNKED_SRC)));
+    }
+
+    fn scan_err(lex: &mut LexStatus) -> LexError {
+        match lex.scan() {
+            Err(e) => e,
+            Ok(_) => panic!("lexical error expected"),
+        }
+    }
+
+    #[test]
+    fn lex_scan_read_error() {
+        let mut lex = LexStatus::from_read(ByteReader {
+            src: b"x = 'abc",
+            fail: true,
+        });
+        lex.setup();
+
+        assert!(lex.scan().is_ok());
+        assert!(lex.scan().is_ok());
+        assert_eq!(
+            scan_err(&mut lex).kind,
+            LexErrorKind::ReadFailed(io::ErrorKind::PermissionDenied)
+        );
+
+        let mut lex = LexStatus::from_read(ByteReader {
+            src: b"x",
+            fail: true,
+        });
+        lex.setup();
+
+        assert!(lex.scan().is_ok());
+        assert_eq!(
+            scan_err(&mut lex).to_string(),
+            "1:2: cannot read source (permission denied) near '<eof>'"
+        );
+    }
+
+    #[test]
+    fn lex_scan_bytes() {
+        let mut lex = LexStatus::new(&b"x = '\xe9t\xe9' .. \"\\xff\\x00\\u{e9}\" -- \xff\n"[..]);
+        lex.setup();
+
+        assert!(lex.scan() == Ok(name_token(&mut lex, "x")));
+        assert!(lex.scan() == Ok(LexToken::Assign));
+        assert!(lex.scan() == Ok(short_str(&mut lex, b"\xe9t\xe9")));
+        assert!(lex.scan() == Ok(LexToken::Concat));
+        assert!(lex.scan() == Ok(short_str(&mut lex, b"\xff\x00\xc3\xa9")));
+        assert!(lex.scan() == Ok(LexToken::Eof));
+    }
+
+    fn scan_lossless_all<'a>(lex: &mut LexStatus<'a>) -> Vec<LosslessToken<'a>> {
+        let mut ret = Vec::new();
+        loop {
+            let token = lex.scan_lossless().unwrap();
+            let eof = token.token.token == LexToken::Eof;
+            ret.push(token);
+            if eof {
+                return ret;
+            }
+        }
+    }
+
+    fn assert_round_trip(src: &[u8]) {
+        let mut lex = LexStatus::new(src);
+        // 被跳过的BOM及shebang同样应被还原
+        lex.set_skip_header(true);
+        lex.setup();
+
+        let mut out = Vec::new();
+        for token in scan_lossless_all(&mut lex) {
+            token.write_to(&mut out);
+        }
+        assert_eq!(out, src);
+    }
+
+    #[test]
+    fn lex_scan_lossless_round_trip() {
+        assert_round_trip(b"");
+        assert_round_trip(b"  \n\t");
+        assert_round_trip(b"local x = 1 -- one\r\nreturn x\n");
+        assert_round_trip(b"--[==[ long\n comment ]==]x=[[\r\nstr\n]]..'\\x41\\z\n  b'");
+        assert_round_trip(b"a\x0b\x0cb \n\r\n\r c -- eof comment");
+        assert_round_trip(CHUNKED_SRC.as_bytes());
+    }
+
+    #[test]
+    fn lex_scan_lossless_trivia() {
+        let mut lex = LexStatus::new("-- head\nlocal x = 'a' -- tail\n\n  --[[ c ]] y - -1");
+        lex.setup();
+        let tokens = scan_lossless_all(&mut lex);
+
+        let kinds = |t: &[Trivia]| t.iter().map(|x| x.kind).collect::<Vec<_>>();
+
+        assert!(tokens[0].token.token == LexToken::Local);
+        assert_eq!(
+            kinds(&tokens[0].leading),
+            vec![TriviaKind::LineComment, TriviaKind::Newline]
+        );
+        assert_eq!(tokens[0].leading[0].text, b"-- head");
+        assert_eq!(tokens[0].leading[0].span.start.offset, 0);
+        assert_eq!(tokens[0].leading[0].span.end.offset, 7);
+        assert_eq!(kinds(&tokens[0].trailing), vec![TriviaKind::Whitespace]);
+
+        assert!(tokens[3].token.token == short_str(&mut lex, b"a"));
+        assert_eq!(tokens[3].text, b"'a'");
+        assert_eq!(
+            kinds(&tokens[3].trailing),
+            vec![
+                TriviaKind::Whitespace,
+                TriviaKind::LineComment,
+                TriviaKind::Newline
+            ]
+        );
+        assert!(tokens[3].trailing[1].is_comment());
+
+        assert!(tokens[4].token.token == name_token(&mut lex, "y"));
+        assert_eq!(
+            kinds(&tokens[4].leading),
+            vec![
+                TriviaKind::Newline,
+                TriviaKind::Whitespace,
+                TriviaKind::LongComment,
+                TriviaKind::Whitespace
+            ]
+        );
+        assert_eq!(tokens[4].leading[2].text, b"--[[ c ]]");
+        assert_eq!(tokens[4].leading[2].span.start.line, 4);
+
+        assert!(tokens[5].token.token == LexToken::Sub);
+        assert!(tokens[6].token.token == LexToken::Sub);
+        assert!(tokens[6].trailing.is_empty());
+        assert!(tokens[8].token.token == LexToken::Eof);
+        assert!(tokens[8].text.is_empty());
+    }
+
+    #[test]
+    fn lex_scan_lossless_chunks() {
+        let chunks = CHUNKED_SRC.as_bytes().chunks(1).map(|c| c.to_vec());
+        let mut lex = LexStatus::from_chunks(chunks);
+        lex.setup();
+
+        let mut out = Vec::new();
+        for token in scan_lossless_all(&mut lex) {
+            token.write_to(&mut out);
+        }
+        assert_eq!(out, CHUNKED_SRC.as_bytes());
+    }
+
+    #[test]
+    fn lex_scan_header() {
+        let mut lex = LexStatus::new("#!/usr/bin/env vine\nlocal x\n  #x");
+        lex.set_skip_header(true);
+        lex.setup();
+        assert!(lex.scan() == Ok(LexToken::Local));
+        assert_eq!(lex.line_number(), 2);
+        assert!(lex.scan() == Ok(name_token(&mut lex, "x")));
+        assert!(lex.scan() == Ok(LexToken::Len));
+        assert_eq!(lex.line_number(), 3);
+
+        let mut lex = LexStatus::new(&b"\xef\xbb\xbfreturn 1"[..]);
+        lex.set_skip_header(true);
+        lex.setup();
+        let t = lex.scan_spanned().unwrap();
+        assert!(t.token == LexToken::Return);
+        assert_eq!(t.span.start, Position::new(3, 1, 1));
+
+        let mut lex = LexStatus::new(&b"\xef\xbb\xbf# comment\r\n\r\nx = 'a"[..]);
+        lex.set_skip_header(true);
+        lex.setup();
+        assert!(lex.scan() == Ok(name_token(&mut lex, "x")));
+        assert!(lex.scan() == Ok(LexToken::Assign));
+        assert_eq!(
+            scan_err(&mut lex).to_string(),
+            "3:7: unfinished string near '<eof>'"
+        );
+
+        let mut lex = LexStatus::new("#");
+        lex.set_skip_header(true);
+        lex.setup();
+        assert!(lex.scan() == Ok(LexToken::Eof));
+
+        // BOM被拆分在两段源码中时同样会被跳过
+        let mut lex = LexStatus::from_chunks(vec![&b"\xef\xbb"[..], b"\xbfreturn"]);
+        lex.set_skip_header(true);
+        lex.setup();
+        let t = lex.scan_spanned().unwrap();
+        assert!(t.token == LexToken::Return);
+        assert_eq!(t.span.start, Position::new(3, 1, 1));
+
+        // 不完整的BOM不会被跳过，0xEF与之后的字节一起构成名字
+        let mut lex = LexStatus::new(&b"\xef\xbbreturn"[..]);
+        lex.set_skip_header(true);
+        lex.setup();
+        let sym = lex.interner_mut().intern(b"\xef\xbbreturn");
+        assert!(lex.scan() == Ok(LexToken::Name(sym)));
+
+        // 默认不跳过BOM及shebang
+        let mut lex = LexStatus::new(&b"\xef\xbb\xbf#x"[..]);
+        lex.setup();
+        let sym = lex.interner_mut().intern(b"\xef\xbb\xbf");
+        assert!(lex.scan() == Ok(LexToken::Name(sym)));
+        assert!(lex.scan() == Ok(LexToken::Len));
+    }
+
+    #[test]
+    fn lex_scan_lossless_header() {
+        let src = b"\xef\xbb\xbf#!/bin/vine\nprint(1)";
+        assert_round_trip(src);
+        assert_round_trip(b"#");
+        assert_round_trip(b"\xef\xbb");
+
+        let mut lex = LexStatus::new(&src[..]);
+        lex.set_skip_header(true);
+        lex.setup();
+        let tokens = scan_lossless_all(&mut lex);
+        let leading = &tokens[0].leading;
+
+        assert!(tokens[0].token.token == name_token(&mut lex, "print"));
+        assert_eq!(leading[0].kind, TriviaKind::Bom);
+        assert_eq!(leading[0].text, b"\xef\xbb\xbf");
+        assert_eq!(leading[1].kind, TriviaKind::Shebang);
+        assert_eq!(leading[1].text, b"#!/bin/vine");
+        assert_eq!(leading[1].span.start, Position::new(3, 1, 1));
+        assert_eq!(leading[2].kind, TriviaKind::Newline);
+        assert_eq!(tokens[0].token.span.start, Position::new(15, 2, 1));
+    }
+
+    fn scan_dialect(s: &str, dialect: Dialect) -> Vec<LexToken<'_>> {
+        let mut lex = LexStatus::new(s);
+        lex.set_dialect(dialect);
+        scan_all(lex)
+    }
+
+    fn scan_dialect_err(s: &str, dialect: Dialect) -> LexError {
+        let mut lex = LexStatus::new(s);
+        lex.set_dialect(dialect);
+        lex.setup();
+        loop {
+            match lex.scan() {
+                Ok(LexToken::Eof) => panic!("{} has no lexical error", s),
+                Ok(_) => continue,
+                Err(e) => return e,
+            }
+        }
+    }
+
+    #[test]
+    fn lex_scan_dialect_keyword() {
+        let mut lex = LexStatus::new("goto");
+        lex.set_dialect(Dialect::Lua51);
+        let goto = name_token(&mut lex, "goto");
+        assert!(scan_all(lex) == vec![goto]);
+        assert!(scan_dialect("goto", Dialect::Lua52) == vec![LexToken::Goto]);
+        assert!(scan_dialect("goto", Dialect::LuaJit) == vec![LexToken::Goto]);
+
+        assert!(
+            scan_dialect("::", Dialect::Lua51) == vec![LexToken::MethodCall, LexToken::MethodCall]
+        );
+        assert!(scan_dialect("::", Dialect::LuaJit) == vec![LexToken::Label]);
+        assert!(scan_dialect("until", Dialect::Lua51) == vec![LexToken::Until]);
+    }
+
+    #[test]
+    fn lex_scan_dialect_operator() {
+        for dialect in [Dialect::Lua51, Dialect::Lua52, Dialect::LuaJit] {
+            assert!(scan_dialect("//", dialect) == vec![LexToken::Div, LexToken::Div]);
+            assert!(scan_dialect("<<", dialect) == vec![LexToken::Less, LexToken::Less]);
+            assert!(scan_dialect(">>", dialect) == vec![LexToken::Greate, LexToken::Greate]);
+            assert!(scan_dialect("~=", dialect) == vec![LexToken::NotEqual]);
+
+            for s in ["a & b", "a | b", "~a", "a ~ b"] {
+                assert_eq!(
+                    scan_dialect_err(s, dialect).kind,
+                    LexErrorKind::UnexpectedSymbol
+                );
+            }
+        }
+        assert_eq!(
+            scan_dialect_err("a ~ b", Dialect::Lua52).to_string(),
+            "1:4: unexpected symbol near '~'"
+        );
+
+        assert!(scan_dialect("//", Dialect::Lua53) == vec![LexToken::FloorDiv]);
+        assert!(scan_dialect("~", Dialect::Lua53) == vec![LexToken::BitXor]);
+    }
+
+    #[test]
+    fn lex_scan_dialect_escape() {
+        let str_of = |s: &str, dialect: Dialect| {
+            let mut lex = LexStatus::new(s);
+            lex.set_dialect(dialect);
+            lex.setup();
+            let token = lex.scan();
+            str_value(&lex, token)
+        };
+
+        // Lua 5.1中无法识别的转义序列得到字符本身
+        assert_eq!(str_of(r"'\x41\z\q'", Dialect::Lua51), b"x41zq");
+        assert_eq!(str_of(r"'\x41\z  b'", Dialect::Lua52), b"Ab");
+        assert_eq!(
+            scan_dialect_err(r"'\u{41}'", Dialect::Lua52).kind,
+            LexErrorKind::InvalidEscape(LexEscapeError::Invalid)
+        );
+
+        assert_eq!(str_of(r"'\u{10FFFF}'", Dialect::Lua53), b"\xf4\x8f\xbf\xbf");
+        assert_eq!(
+            scan_dialect_err(r"'\u{110000}'", Dialect::Lua53).kind,
+            LexErrorKind::InvalidEscape(LexEscapeError::Utf8TooLarge)
+        );
+        assert_eq!(
+            scan_dialect_err(r"'\u{110000}'", Dialect::LuaJit).kind,
+            LexErrorKind::InvalidEscape(LexEscapeError::Utf8TooLarge)
+        );
+        assert_eq!(str_of(r"'\u{7FFFFFFF}'", Dialect::Lua54).len(), 6);
+    }
+
+    #[test]
+    fn lex_scan_dialect_number() {
+        let num_of = |s: &str, dialect: Dialect| match scan_dialect(s, dialect).pop() {
+            Some(LexToken::Number(x)) => x,
+            _ => panic!("number expected"),
+        };
+
+        assert!(num_of("10", Dialect::Lua53) == LexNumberValue::Int(10));
+        assert!(num_of("10", Dialect::Lua52) == LexNumberValue::Float(10.0));
+        assert!(num_of("0x10", Dialect::LuaJit) == LexNumberValue::Float(16.0));
+        assert!(
+            num_of("0xffffffffffffffff", Dialect::Lua52)
+                == LexNumberValue::Float(18446744073709551615.0)
+        );
+        assert!(num_of("0xffffffffffffffff", Dialect::Lua54) == LexNumberValue::Int(-1));
+
+        assert!(num_of("0x1p4", Dialect::Lua52) == LexNumberValue::Float(16.0));
+        assert!(num_of("0x10", Dialect::Lua51) == LexNumberValue::Float(16.0));
+        for s in ["0x1p4", "0x.8"] {
+            assert_eq!(
+                scan_dialect_err(s, Dialect::Lua51).kind,
+                LexErrorKind::MalformedNumber
+            );
+        }
+    }
+
+    #[test]
+    fn lex_scan_luau_compound_assignment() {
+        let tokens = scan_dialect("a += 1 -= *= /= //= %= ^= ..= ... -- c", Dialect::Luau);
+        assert!(tokens[1] == LexToken::AddAssign);
+        assert!(tokens[3] == LexToken::SubAssign);
+        assert!(tokens[4] == LexToken::MulAssign);
+        assert!(tokens[5] == LexToken::DivAssign);
+        assert!(tokens[6] == LexToken::FloorDivAssign);
+        assert!(tokens[7] == LexToken::ModAssign);
+        assert!(tokens[8] == LexToken::PowAssign);
+        assert!(tokens[9] == LexToken::ConcatAssign);
+        assert!(tokens[10] == LexToken::Dots);
+        assert_eq!(tokens.len(), 11);
+
+        let tokens = scan_dialect("a += 1", Dialect::Lua54);
+        assert!(tokens[1] == LexToken::Add);
+        assert!(tokens[2] == LexToken::Assign);
+
+        // continue是上下文关键字，由语法分析判断
+        let mut lex = LexStatus::new("continue");
+        lex.set_dialect(Dialect::Luau);
+        let name = name_token(&mut lex, "continue");
+        assert!(scan_all(lex) == vec![name]);
+    }
+
+    #[test]
+    fn lex_scan_luau_interpolated_string() {
+        let tokens = scan_dialect("`x = {x}, t = { {1}[1]}!\\{\\`` `plain`", Dialect::Luau);
+        assert!(tokens[0] == LexToken::InterpBegin(b"x = ".to_vec().into()));
+        assert!(matches!(tokens[1], LexToken::Name(_)));
+        assert!(tokens[2] == LexToken::InterpMid(b", t = ".to_vec().into()));
+        assert!(tokens[3] == LexToken::CurlyBracketLeft);
+        assert!(matches!(tokens[4], LexToken::Number(_)));
+        assert!(tokens[5] == LexToken::CurlyBracketRight);
+        assert!(tokens[6] == LexToken::SquareBracketLeft);
+        assert!(tokens[8] == LexToken::SquareBracketRight);
+        assert!(tokens[9] == LexToken::InterpEnd(b"!{`".to_vec().into()));
+        assert!(tokens[10] == LexToken::InterpSimple(b"plain".to_vec().into()));
+        assert_eq!(tokens.len(), 11);
+
+        let tokens = scan_dialect("`a{`b{c}`}`", Dialect::Luau);
+        assert!(tokens[1] == LexToken::InterpBegin(b"b".to_vec().into()));
+        assert!(tokens[3] == LexToken::InterpEnd(Vec::new().into()));
+        assert!(tokens[4] == LexToken::InterpEnd(Vec::new().into()));
+
+        assert_eq!(
+            scan_dialect_err("`a{{b}}`", Dialect::Luau).to_string(),
+            "1:5: double braces are not permitted within interpolated strings near '`a{{'"
+        );
+        assert_eq!(
+            scan_dialect_err("`a{b}c", Dialect::Luau).kind,
+            LexErrorKind::UnfinishedString
+        );
+        assert_eq!(
+            scan_dialect_err("`a", Dialect::Lua54).kind,
+            LexErrorKind::UnexpectedSymbol
+        );
+    }
+
+    #[test]
+    fn lex_scan_luau_lossless() {
+        let src = "local s = `{a} -- {b}`\ns ..= `x` -- c\n";
+        let mut lex = LexStatus::new(src);
+        lex.set_dialect(Dialect::Luau);
+        lex.setup();
+
+        let mut out = Vec::new();
+        for token in scan_lossless_all(&mut lex) {
+            token.write_to(&mut out);
+        }
+        assert_eq!(out, src.as_bytes());
+    }
+
+    // 超过MAX_SHORT_LEN的字符串
+    const LONG_STR: &str = "long string that is not interned by the lexer";
+
+    #[test]
+    fn lex_scan_interned() {
+        let mut lex = LexStatus::new("local x = x .. 'x' .. [[x]] .. \"\\120\"");
+        lex.setup();
+
+        let x = name_token(&mut lex, "x");
+        assert!(lex.scan() == Ok(LexToken::Local));
+        assert!(lex.scan() == Ok(name_token(&mut lex, "x")));
+        assert!(lex.scan() == Ok(LexToken::Assign));
+        assert!(lex.scan() == Ok(x));
+
+        // 名字与短字符串共享同一张驻留表
+        let s = short_str(&mut lex, b"x");
+        for _ in 0..3 {
+            assert!(lex.scan() == Ok(LexToken::Concat));
+            assert!(lex.scan() == Ok(short_str(&mut lex, b"x")));
+        }
+        assert!(lex.scan() == Ok(LexToken::Eof));
+        assert!(s == short_str(&mut lex, b"x"));
+        assert_eq!(lex.interner().len(), 1);
+
+        // 驻留表可以在多个LexStatus间共享
+        let interner = lex.into_interner();
+        let sym = interner.get("x").unwrap();
+        let mut lex = LexStatus::new("y x");
+        lex.set_interner(interner);
+        lex.setup();
+        assert!(lex.scan() == Ok(name_token(&mut lex, "y")));
+        assert!(lex.scan() == Ok(LexToken::Name(sym)));
+        assert_eq!(lex.interner().resolve_str(sym), "x");
+
+        let long = format!("'{}' [[{}]]", LONG_STR, LONG_STR);
+        let mut lex = LexStatus::new(&long);
+        lex.setup();
+        assert!(lex.scan() == Ok(LexToken::Str(LONG_STR.as_bytes().into())));
+        assert!(lex.scan() == Ok(LexToken::Str(LONG_STR.as_bytes().into())));
+        assert!(lex.interner().is_empty());
+    }
+
+    #[test]
+    fn lex_scan_borrowed() {
+        let src = format!(
+            "'{}' .. \"\\t{}\" .. [==[\n{}]==] .. [[\r\n{}\r\n]]",
+            LONG_STR, LONG_STR, LONG_STR, LONG_STR
+        );
+        let mut lex = LexStatus::new(&src);
+        lex.setup();
+
+        match lex.scan() {
+            Ok(LexToken::Str(Cow::Borrowed(x))) => assert_eq!(x, LONG_STR.as_bytes()),
+            _ => panic!("borrowed string expected"),
+        }
+        assert!(lex.scan() == Ok(LexToken::Concat));
+        match lex.scan() {
+            Ok(LexToken::Str(Cow::Owned(x))) => assert_eq!(x, format!("\t{}", LONG_STR).as_bytes()),
+            _ => panic!("owned string expected"),
+        }
+        assert!(lex.scan() == Ok(LexToken::Concat));
+        match lex.scan() {
+            Ok(LexToken::Str(Cow::Borrowed(x))) => assert_eq!(x, LONG_STR.as_bytes()),
+            _ => panic!("borrowed string expected"),
+        }
+        assert!(lex.scan() == Ok(LexToken::Concat));
+        match lex.scan() {
+            Ok(LexToken::Str(Cow::Owned(x))) => assert_eq!(x, format!("{}\n", LONG_STR).as_bytes()),
+            _ => panic!("owned string expected"),
+        }
+        assert!(lex.scan() == Ok(LexToken::Eof));
+    }
+
+    #[test]
+    fn lex_scan_borrowed_chunks() {
+        // 完整位于借用的源码段中的Token仍然借用源码，跨越源码段的Token会被复制
+        let (head, tail) = LONG_STR.split_at(10);
+        let first = format!("'{}' '{}", LONG_STR, head);
+        let second = format!("{}'", tail);
+        let mut lex = LexStatus::from_chunks(vec![first.as_bytes(), second.as_bytes()]);
+        lex.setup();
+
+        assert!(matches!(lex.scan(), Ok(LexToken::Str(Cow::Borrowed(_)))));
+        match lex.scan() {
+            Ok(LexToken::Str(Cow::Owned(x))) => assert_eq!(x, LONG_STR.as_bytes()),
+            _ => panic!("owned string expected"),
+        }
+
+        let src = "[==[\r\n\n\r\r]==] 'a\\x41\\z\n b' 12.5e1 0x";
+        let n = src.len() - 2;
+        let mut lex = LexStatus::from_chunks(src.as_bytes()[..n].chunks(1));
+        lex.setup();
+        assert!(lex.scan() == Ok(short_str(&mut lex, b"\n\n")));
+        assert!(lex.scan() == Ok(short_str(&mut lex, b"aAb")));
+        assert!(lex.scan() == Ok(LexToken::Number(LexNumberValue::Float(125.0))));
+
+        let mut lex = LexStatus::from_chunks(src.as_bytes().chunks(3));
+        lex.setup();
+        let e = loop {
+            if let Err(e) = lex.scan() {
+                break e;
+            }
+        };
+        assert_eq!(e.to_string(), "5:14: malformed number near '0x'");
+    }
+
+    fn scan_recovery(s: &str, dialect: Dialect) -> Vec<String> {
+        let mut lex = LexStatus::new(s);
+        lex.set_dialect(dialect);
+        lex.set_recovery(true);
+        scan_all(lex)
+            .into_iter()
+            .map(|token| match token {
+                LexToken::Error(e) => e.to_string(),
+                token => token.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn lex_scan_recovery() {
+        let src = "a = 'x\\q\\400' .. 0x1g .. '\\400'\nb = \"open\nc = $ + [=x ~= 1\nd = [[never";
+        assert_eq!(
+            scan_recovery(src, Dialect::Lua54),
+            [
+                "<name>",
+                "'='",
+                "1:9: invalid escape sequence near ''x\\q'",
+                "'..'",
+                "1:22: malformed number near '0x1g'",
+                "'..'",
+                "1:32: decimal escape too large near ''\\400''",
+                "<name>",
+                "'='",
+                "2:10: unfinished string near '\"open'",
+                "<name>",
+                "'='",
+                "3:5: unexpected symbol near '$'",
+                "'+'",
+                "3:11: invalid long string delimiter near '[='",
+                "<name>",
+                "'~='",
+                "<integer>",
+                "<name>",
+                "'='",
+                "4:12: unfinished long string (starting at line 4) near '<eof>'",
+            ]
+        );
+
+        // 插值字符串出错后，插值表达式及之后的部分仍然照常扫描
+        assert_eq!(
+            scan_recovery("s = `a\\q{x}b{{c`\nt = 1", Dialect::Luau),
+            [
+                "<name>",
+                "'='",
+                "1:9: invalid escape sequence near '`a\\q'",
+                "<name>",
+                "1:15: double braces are not permitted within interpolated strings near '}b{{'",
+                "<name>",
+                "'='",
+                "<number>",
+            ]
+        );
+
+        // 未开启恢复模式时仍在第一个错误处中止
+        let mut lex = LexStatus::new(src);
+        lex.setup();
+        lex.scan().unwrap();
+        lex.scan().unwrap();
+        assert_eq!(
+            scan_err(&mut lex).kind,
+            LexErrorKind::InvalidEscape(LexEscapeError::Invalid)
+        );
+    }
+
+    #[test]
+    fn lex_char_classes() {
+        assert!(CHAR_CLASSES[b'a' as usize] == CharClass::Name);
+        assert!(CHAR_CLASSES[b'_' as usize] == CharClass::Name);
+        assert!(CHAR_CLASSES[0xe4] == CharClass::Name);
+        assert!(CHAR_CLASSES[b'7' as usize] == CharClass::Digit);
+        assert!(CHAR_CLASSES[b'\r' as usize] == CharClass::Newline);
+        assert!(CHAR_CLASSES[0x0b] == CharClass::Space);
+        assert!(CHAR_CLASSES[b'$' as usize] == CharClass::Punct);
+        assert!(CHAR_CLASSES[0x7f] == CharClass::Other);
     }
 }
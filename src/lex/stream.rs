@@ -0,0 +1,180 @@
+use super::error::LexError;
+use super::span::SpannedToken;
+use super::state::LexStatus;
+use super::token::LexToken;
+use std::collections::VecDeque;
+
+// 基于LexStatus的Token流，支持向前查看任意多个Token
+//
+// Token流以Eof结束；遇到词法错误时返回该错误，之后不再产生Token
+pub struct TokenStream<'src_lt> {
+    lex: LexStatus<'src_lt>,
+    lookahead: VecDeque<Result<SpannedToken<'src_lt>, LexError>>,
+    finished: bool,
+}
+
+impl<'src_lt> TokenStream<'src_lt> {
+    // 构造新的TokenStream
+    //
+    // @param src: 传入的Lua源码
+    //
+    // @return: TokenStream
+    pub fn new<S: AsRef<[u8]> + ?Sized>(src: &'src_lt S) -> Self {
+        TokenStream::from_status(LexStatus::new(src))
+    }
+
+    // 使用一个尚未setup的LexStatus构造TokenStream
+    //
+    // @param lex: 词法分析状态器
+    //
+    // @return: TokenStream
+    pub fn from_status(mut lex: LexStatus<'src_lt>) -> Self {
+        lex.setup();
+
+        TokenStream {
+            lex,
+            lookahead: VecDeque::new(),
+            finished: false,
+        }
+    }
+
+    // 从LexStatus中扫描下一个Token
+    //
+    // @return: Token，Token流结束时返回None
+    fn scan(&mut self) -> Option<Result<SpannedToken<'src_lt>, LexError>> {
+        if self.finished {
+            return None;
+        }
+
+        let token = self.lex.scan_spanned();
+        match token {
+            Ok(SpannedToken {
+                token: LexToken::Eof,
+                ..
+            })
+            | Err(_) => self.finished = true,
+            _ => {}
+        }
+
+        Some(token)
+    }
+
+    // 查看下一个Token，但不消耗它
+    //
+    // @return: 下一个Token
+    pub fn peek(&mut self) -> Option<&Result<SpannedToken<'src_lt>, LexError>> {
+        self.peek_nth(0)
+    }
+
+    // 查看之后的第k个Token(从0开始)，但不消耗它
+    //
+    // @param k: 向前查看的距离
+    //
+    // @return: 第k个Token，Token流在此之前结束时返回None
+    pub fn peek_nth(&mut self, k: usize) -> Option<&Result<SpannedToken<'src_lt>, LexError>> {
+        while self.lookahead.len() <= k {
+            match self.scan() {
+                Some(token) => self.lookahead.push_back(token),
+                None => return None,
+            }
+        }
+
+        self.lookahead.get(k)
+    }
+
+    // 获取底层的词法分析状态器
+    //
+    // @return: LexStatus
+    pub fn status(&self) -> &LexStatus<'src_lt> {
+        &self.lex
+    }
+
+    // 获取可修改的底层词法分析状态器；已被向前查看的Token不受修改影响
+    //
+    // @return: LexStatus
+    pub fn status_mut(&mut self) -> &mut LexStatus<'src_lt> {
+        &mut self.lex
+    }
+
+    // 取出底层的词法分析状态器
+    //
+    // @return: LexStatus
+    pub fn into_status(self) -> LexStatus<'src_lt> {
+        self.lex
+    }
+}
+
+impl<'src_lt> Iterator for TokenStream<'src_lt> {
+    type Item = Result<SpannedToken<'src_lt>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.lookahead.pop_front() {
+            Some(token) => Some(token),
+            None => self.scan(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::LexErrorKind;
+
+    fn token_of<'a, 'src_lt>(
+        t: Option<&'a Result<SpannedToken<'src_lt>, LexError>>,
+    ) -> &'a LexToken<'src_lt> {
+        match t {
+            Some(Ok(t)) => &t.token,
+            _ => panic!("not a token"),
+        }
+    }
+
+    fn name_of<'a>(stream: &TokenStream, s: &str) -> LexToken<'a> {
+        LexToken::Name(stream.status().interner().get(s).unwrap())
+    }
+
+    #[test]
+    fn token_stream_iter() {
+        let mut stream = TokenStream::new("local x = 1");
+        let tokens: Vec<LexToken> = stream.by_ref().map(|t| t.unwrap().token).collect();
+
+        assert!(tokens[0] == LexToken::Local);
+        assert!(tokens[1] == name_of(&stream, "x"));
+        assert!(tokens[2] == LexToken::Assign);
+        assert!(matches!(tokens[3], LexToken::Number(_)));
+        assert!(tokens[4] == LexToken::Eof);
+        assert_eq!(tokens.len(), 5);
+    }
+
+    #[test]
+    fn token_stream_peek() {
+        let mut stream = TokenStream::new("a : b");
+
+        stream.peek_nth(2);
+        let (a, b) = (name_of(&stream, "a"), name_of(&stream, "b"));
+        assert!(*token_of(stream.peek_nth(2)) == b);
+        assert!(*token_of(stream.peek()) == a);
+        assert!(*token_of(stream.peek_nth(1)) == LexToken::MethodCall);
+        assert!(stream.peek_nth(4).is_none());
+
+        assert!(stream.next().unwrap().unwrap().token == a);
+        assert!(*token_of(stream.peek()) == LexToken::MethodCall);
+        assert!(*token_of(stream.peek_nth(2)) == LexToken::Eof);
+        assert_eq!(stream.by_ref().count(), 3);
+        assert!(stream.next().is_none());
+        assert!(stream.peek().is_none());
+    }
+
+    #[test]
+    fn token_stream_error() {
+        let mut stream = TokenStream::new("x = 'abc");
+
+        assert!(stream.next().unwrap().is_ok());
+        assert!(stream.next().unwrap().is_ok());
+        match stream.next() {
+            Some(Err(e)) => assert_eq!(e.kind, LexErrorKind::UnfinishedString),
+            _ => panic!("unfinished string expected"),
+        }
+        assert!(stream.next().is_none());
+    }
+}
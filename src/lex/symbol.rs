@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// 短字符串的最大长度，与Lua的LUAI_MAXSHORTLEN一致；不超过该长度的字符串常量会被驻留
+pub const MAX_SHORT_LEN: usize = 40;
+
+// 驻留字符串的编号，同一Interner中相同内容的字符串编号相同
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Symbol(u32);
+
+// 字符串驻留表，供词法分析、语法分析及编译共享；
+// 编号即字符串在表中的下标，虚拟机的字符串表可以直接按编号接管其中的字符串
+#[derive(Debug, Default)]
+pub struct Interner {
+    map: HashMap<Rc<[u8]>, Symbol>,
+    strings: Vec<Rc<[u8]>>,
+}
+
+impl Symbol {
+    // 获取编号对应的下标
+    //
+    // @return: 下标
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl Interner {
+    // 构造新的Interner
+    //
+    // @return: Interner
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    // 驻留一个字符串，字符串已存在时不会再分配
+    //
+    // @param s: 字符串
+    //
+    // @return: 字符串的编号
+    pub fn intern<S: AsRef<[u8]> + ?Sized>(&mut self, s: &S) -> Symbol {
+        let s = s.as_ref();
+        if let Some(sym) = self.map.get(s) {
+            return *sym;
+        }
+
+        let sym = Symbol(self.strings.len() as u32);
+        let s: Rc<[u8]> = Rc::from(s);
+        self.strings.push(Rc::clone(&s));
+        self.map.insert(s, sym);
+
+        sym
+    }
+
+    // 查找已驻留的字符串，不存在时不会驻留
+    //
+    // @param s: 字符串
+    //
+    // @return: 字符串的编号
+    pub fn get<S: AsRef<[u8]> + ?Sized>(&self, s: &S) -> Option<Symbol> {
+        self.map.get(s.as_ref()).copied()
+    }
+
+    // 获取编号对应的字符串
+    //
+    // @param sym: 字符串的编号
+    //
+    // @return: 字符串
+    pub fn resolve(&self, sym: Symbol) -> &[u8] {
+        &self.strings[sym.index()]
+    }
+
+    // 获取编号对应的字符串，非UTF-8的字节序列以U+FFFD代替
+    //
+    // @param sym: 字符串的编号
+    //
+    // @return: 字符串
+    pub fn resolve_str(&self, sym: Symbol) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.resolve(sym))
+    }
+
+    // 已驻留的字符串个数
+    //
+    // @return: 个数
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    // 是否没有驻留任何字符串
+    //
+    // @return: 是否为空
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    // 按编号顺序取出全部字符串
+    //
+    // @return: 字符串，下标即编号
+    pub fn into_strings(self) -> Vec<Rc<[u8]>> {
+        self.strings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interner_intern() {
+        let mut interner = Interner::new();
+        assert!(interner.is_empty());
+
+        let a = interner.intern("local");
+        let b = interner.intern(&b"\xff"[..]);
+        assert_eq!(interner.intern("local"), a);
+        assert_ne!(a, b);
+        assert_eq!(interner.len(), 2);
+
+        assert_eq!(interner.get("local"), Some(a));
+        assert_eq!(interner.get("x"), None);
+        assert_eq!(interner.resolve(b), b"\xff");
+        assert_eq!(interner.resolve_str(a), "local");
+        assert_eq!(interner.resolve_str(b), "\u{fffd}");
+
+        let strings = interner.into_strings();
+        assert_eq!(&*strings[a.index()], b"local");
+        assert_eq!(&*strings[b.index()], b"\xff");
+    }
+}
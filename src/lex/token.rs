@@ -1,12 +1,30 @@
+use super::error::LexError;
 pub use super::number::LexNumberValue;
+use super::symbol::{Interner, Symbol};
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
 
-pub enum LexToken {
+// 词法单元；名字及不超过MAX_SHORT_LEN的字符串常量驻留为Symbol，
+// 更长的字符串尽可能直接借用源码，只有含转义序列等必须改写时才另行分配
+#[derive(Debug)]
+pub enum LexToken<'src_lt> {
     Add,
+    AddAssign,
     And,
     Assign,
+    BitAnd,
+    BitOr,
+    // 二元按位异或，同时作为一元按位取反
+    BitXor,
     Break,
+    Comma,
     Concat,
+    ConcatAssign,
+    CurlyBracketLeft,
+    CurlyBracketRight,
     Div,
+    DivAssign,
     Do,
     Dot,
     Dots,
@@ -15,7 +33,11 @@ pub enum LexToken {
     End,
     Eof,
     Equal,
+    // 恢复模式下无法识别的一段源码，携带对应的词法错误
+    Error(Box<LexError>),
     False,
+    FloorDiv,
+    FloorDivAssign,
     For,
     Function,
     Goto,
@@ -23,37 +45,556 @@ pub enum LexToken {
     GreateEqual,
     If,
     In,
+    // Luau插值字符串`a{b}c{d}e`依次扫描为InterpBegin(a)、b、InterpMid(c)、d、InterpEnd(e)，
+    // 不含插值表达式的`abc`扫描为InterpSimple(abc)
+    InterpBegin(Cow<'src_lt, [u8]>),
+    InterpEnd(Cow<'src_lt, [u8]>),
+    InterpMid(Cow<'src_lt, [u8]>),
+    InterpSimple(Cow<'src_lt, [u8]>),
     Label,
     Less,
     LessEqual,
+    Len,
     Local,
     MethodCall,
     Mod,
+    ModAssign,
     Mul,
-    Name(String),
+    MulAssign,
+    Name(Symbol),
     Nil,
     Not,
     NotEqual,
     Number(LexNumberValue),
     Or,
+    Pow,
+    PowAssign,
     Repeat,
     Return,
+    RoundBracketLeft,
+    RoundBracketRight,
+    Semicolon,
+    ShortStr(Symbol),
+    ShiftLeft,
+    ShiftRight,
     SquareBracketLeft,
-    Str(String),
+    SquareBracketRight,
+    Str(Cow<'src_lt, [u8]>),
     Sub,
+    SubAssign,
     Then,
     True,
-    Util,
+    Until,
     While,
 }
 
-impl PartialEq for LexToken {
+impl PartialEq for LexToken<'_> {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::Number(l0), Self::Number(r0)) => l0 == r0,
-            (Self::Name(l0), Self::Name(r0)) => l0 == r0,
-            (Self::Str(l0), Self::Str(r0)) => l0 == r0,
+            (Self::Error(l0), Self::Error(r0)) => l0 == r0,
+            (Self::Name(l0), Self::Name(r0)) | (Self::ShortStr(l0), Self::ShortStr(r0)) => l0 == r0,
+            (Self::Str(l0), Self::Str(r0))
+            | (Self::InterpBegin(l0), Self::InterpBegin(r0))
+            | (Self::InterpEnd(l0), Self::InterpEnd(r0))
+            | (Self::InterpMid(l0), Self::InterpMid(r0))
+            | (Self::InterpSimple(l0), Self::InterpSimple(r0)) => l0 == r0,
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
 }
+
+// 将Token按Lua源码的形式写出，名字与短字符串需要从Interner中取得
+pub struct LexTokenSource<'a, 'src_lt> {
+    token: &'a LexToken<'src_lt>,
+    interner: &'a Interner,
+}
+
+impl<'src_lt> LexToken<'src_lt> {
+    // 获取关键字及运算符等内容固定的Token在源码中的写法
+    //
+    // @return: Token的写法，名字、字符串、数字、Error及Eof返回None
+    pub fn text(&self) -> Option<&'static str> {
+        Some(match self {
+            LexToken::Add => "+",
+            LexToken::AddAssign => "+=",
+            LexToken::And => "and",
+            LexToken::Assign => "=",
+            LexToken::BitAnd => "&",
+            LexToken::BitOr => "|",
+            LexToken::BitXor => "~",
+            LexToken::Break => "break",
+            LexToken::Comma => ",",
+            LexToken::Concat => "..",
+            LexToken::ConcatAssign => "..=",
+            LexToken::CurlyBracketLeft => "{",
+            LexToken::CurlyBracketRight => "}",
+            LexToken::Div => "/",
+            LexToken::DivAssign => "/=",
+            LexToken::Do => "do",
+            LexToken::Dot => ".",
+            LexToken::Dots => "...",
+            LexToken::Else => "else",
+            LexToken::ElseIf => "elseif",
+            LexToken::End => "end",
+            LexToken::Equal => "==",
+            LexToken::False => "false",
+            LexToken::FloorDiv => "//",
+            LexToken::FloorDivAssign => "//=",
+            LexToken::For => "for",
+            LexToken::Function => "function",
+            LexToken::Goto => "goto",
+            LexToken::Greate => ">",
+            LexToken::GreateEqual => ">=",
+            LexToken::If => "if",
+            LexToken::In => "in",
+            LexToken::Label => "::",
+            LexToken::Len => "#",
+            LexToken::Less => "<",
+            LexToken::LessEqual => "<=",
+            LexToken::Local => "local",
+            LexToken::MethodCall => ":",
+            LexToken::Mod => "%",
+            LexToken::ModAssign => "%=",
+            LexToken::Mul => "*",
+            LexToken::MulAssign => "*=",
+            LexToken::Nil => "nil",
+            LexToken::Not => "not",
+            LexToken::NotEqual => "~=",
+            LexToken::Or => "or",
+            LexToken::Pow => "^",
+            LexToken::PowAssign => "^=",
+            LexToken::Repeat => "repeat",
+            LexToken::Return => "return",
+            LexToken::RoundBracketLeft => "(",
+            LexToken::RoundBracketRight => ")",
+            LexToken::Semicolon => ";",
+            LexToken::ShiftLeft => "<<",
+            LexToken::ShiftRight => ">>",
+            LexToken::SquareBracketLeft => "[",
+            LexToken::SquareBracketRight => "]",
+            LexToken::Sub => "-",
+            LexToken::SubAssign => "-=",
+            LexToken::Then => "then",
+            LexToken::True => "true",
+            LexToken::Until => "until",
+            LexToken::While => "while",
+            LexToken::Eof
+            | LexToken::Error(_)
+            | LexToken::InterpBegin(_)
+            | LexToken::InterpEnd(_)
+            | LexToken::InterpMid(_)
+            | LexToken::InterpSimple(_)
+            | LexToken::Name(_)
+            | LexToken::Number(_)
+            | LexToken::ShortStr(_)
+            | LexToken::Str(_) => return None,
+        })
+    }
+
+    // 按Lua源码的形式写出Token，字符串会重新转义，写出的内容可以被重新扫描为相同的Token
+    //
+    // @param interner: 扫描该Token时使用的字符串驻留表
+    //
+    // @return: 实现了Display的LexTokenSource；名字含有非UTF-8字节时需使用write_source写出
+    pub fn source<'a>(&'a self, interner: &'a Interner) -> LexTokenSource<'a, 'src_lt> {
+        LexTokenSource {
+            token: self,
+            interner,
+        }
+    }
+
+    // 将借用源码的Token转化为独立拥有其内容的Token，以便在源码被修改或释放后继续保存
+    //
+    // @return: 不再借用源码的Token
+    pub fn into_owned(self) -> LexToken<'static> {
+        match self {
+            LexToken::Add => LexToken::Add,
+            LexToken::AddAssign => LexToken::AddAssign,
+            LexToken::And => LexToken::And,
+            LexToken::Assign => LexToken::Assign,
+            LexToken::BitAnd => LexToken::BitAnd,
+            LexToken::BitOr => LexToken::BitOr,
+            LexToken::BitXor => LexToken::BitXor,
+            LexToken::Break => LexToken::Break,
+            LexToken::Comma => LexToken::Comma,
+            LexToken::Concat => LexToken::Concat,
+            LexToken::ConcatAssign => LexToken::ConcatAssign,
+            LexToken::CurlyBracketLeft => LexToken::CurlyBracketLeft,
+            LexToken::CurlyBracketRight => LexToken::CurlyBracketRight,
+            LexToken::Div => LexToken::Div,
+            LexToken::DivAssign => LexToken::DivAssign,
+            LexToken::Do => LexToken::Do,
+            LexToken::Dot => LexToken::Dot,
+            LexToken::Dots => LexToken::Dots,
+            LexToken::Else => LexToken::Else,
+            LexToken::ElseIf => LexToken::ElseIf,
+            LexToken::End => LexToken::End,
+            LexToken::Eof => LexToken::Eof,
+            LexToken::Equal => LexToken::Equal,
+            LexToken::Error(e) => LexToken::Error(e),
+            LexToken::False => LexToken::False,
+            LexToken::FloorDiv => LexToken::FloorDiv,
+            LexToken::FloorDivAssign => LexToken::FloorDivAssign,
+            LexToken::For => LexToken::For,
+            LexToken::Function => LexToken::Function,
+            LexToken::Goto => LexToken::Goto,
+            LexToken::Greate => LexToken::Greate,
+            LexToken::GreateEqual => LexToken::GreateEqual,
+            LexToken::If => LexToken::If,
+            LexToken::In => LexToken::In,
+            LexToken::InterpBegin(v) => LexToken::InterpBegin(Cow::Owned(v.into_owned())),
+            LexToken::InterpEnd(v) => LexToken::InterpEnd(Cow::Owned(v.into_owned())),
+            LexToken::InterpMid(v) => LexToken::InterpMid(Cow::Owned(v.into_owned())),
+            LexToken::InterpSimple(v) => LexToken::InterpSimple(Cow::Owned(v.into_owned())),
+            LexToken::Label => LexToken::Label,
+            LexToken::Less => LexToken::Less,
+            LexToken::LessEqual => LexToken::LessEqual,
+            LexToken::Len => LexToken::Len,
+            LexToken::Local => LexToken::Local,
+            LexToken::MethodCall => LexToken::MethodCall,
+            LexToken::Mod => LexToken::Mod,
+            LexToken::ModAssign => LexToken::ModAssign,
+            LexToken::Mul => LexToken::Mul,
+            LexToken::MulAssign => LexToken::MulAssign,
+            LexToken::Name(v) => LexToken::Name(v),
+            LexToken::Nil => LexToken::Nil,
+            LexToken::Not => LexToken::Not,
+            LexToken::NotEqual => LexToken::NotEqual,
+            LexToken::Number(v) => LexToken::Number(v),
+            LexToken::Or => LexToken::Or,
+            LexToken::Pow => LexToken::Pow,
+            LexToken::PowAssign => LexToken::PowAssign,
+            LexToken::Repeat => LexToken::Repeat,
+            LexToken::Return => LexToken::Return,
+            LexToken::RoundBracketLeft => LexToken::RoundBracketLeft,
+            LexToken::RoundBracketRight => LexToken::RoundBracketRight,
+            LexToken::Semicolon => LexToken::Semicolon,
+            LexToken::ShortStr(v) => LexToken::ShortStr(v),
+            LexToken::ShiftLeft => LexToken::ShiftLeft,
+            LexToken::ShiftRight => LexToken::ShiftRight,
+            LexToken::SquareBracketLeft => LexToken::SquareBracketLeft,
+            LexToken::SquareBracketRight => LexToken::SquareBracketRight,
+            LexToken::Str(v) => LexToken::Str(Cow::Owned(v.into_owned())),
+            LexToken::Sub => LexToken::Sub,
+            LexToken::SubAssign => LexToken::SubAssign,
+            LexToken::Then => LexToken::Then,
+            LexToken::True => LexToken::True,
+            LexToken::Until => LexToken::Until,
+            LexToken::While => LexToken::While,
+        }
+    }
+}
+
+// 与luaX_token2str一致：关键字及运算符加引号，其余Token以<name>等名称表示
+impl fmt::Display for LexToken<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LexToken::Eof => "<eof>",
+            LexToken::Error(_) => "<error>",
+            LexToken::Name(_) => "<name>",
+            LexToken::Number(LexNumberValue::Int(_)) => "<integer>",
+            LexToken::Number(_) => "<number>",
+            LexToken::Str(_) | LexToken::ShortStr(_) => "<string>",
+            // Luau插值字符串的各段同样以<string>表示
+            LexToken::InterpBegin(_)
+            | LexToken::InterpMid(_)
+            | LexToken::InterpEnd(_)
+            | LexToken::InterpSimple(_) => "<string>",
+            LexToken::Add
+            | LexToken::AddAssign
+            | LexToken::And
+            | LexToken::Assign
+            | LexToken::BitAnd
+            | LexToken::BitOr
+            | LexToken::BitXor
+            | LexToken::Break
+            | LexToken::Comma
+            | LexToken::Concat
+            | LexToken::ConcatAssign
+            | LexToken::CurlyBracketLeft
+            | LexToken::CurlyBracketRight
+            | LexToken::Div
+            | LexToken::DivAssign
+            | LexToken::Do
+            | LexToken::Dot
+            | LexToken::Dots
+            | LexToken::Else
+            | LexToken::ElseIf
+            | LexToken::End
+            | LexToken::Equal
+            | LexToken::False
+            | LexToken::FloorDiv
+            | LexToken::FloorDivAssign
+            | LexToken::For
+            | LexToken::Function
+            | LexToken::Goto
+            | LexToken::Greate
+            | LexToken::GreateEqual
+            | LexToken::If
+            | LexToken::In
+            | LexToken::Label
+            | LexToken::Less
+            | LexToken::LessEqual
+            | LexToken::Len
+            | LexToken::Local
+            | LexToken::MethodCall
+            | LexToken::Mod
+            | LexToken::ModAssign
+            | LexToken::Mul
+            | LexToken::MulAssign
+            | LexToken::Nil
+            | LexToken::Not
+            | LexToken::NotEqual
+            | LexToken::Or
+            | LexToken::Pow
+            | LexToken::PowAssign
+            | LexToken::Repeat
+            | LexToken::Return
+            | LexToken::RoundBracketLeft
+            | LexToken::RoundBracketRight
+            | LexToken::Semicolon
+            | LexToken::ShiftLeft
+            | LexToken::ShiftRight
+            | LexToken::SquareBracketLeft
+            | LexToken::SquareBracketRight
+            | LexToken::Sub
+            | LexToken::SubAssign
+            | LexToken::Then
+            | LexToken::True
+            | LexToken::Until
+            | LexToken::While => {
+                return write!(f, "'{}'", self.text().unwrap_or_default());
+            }
+        })
+    }
+}
+
+// 将字符串写为带引号的Lua字符串，无法直接写出的字节以\ddd转义
+//
+// @param f: 输出
+// @param v: 字符串的值
+// @param interp: 是否为插值字符串，插值字符串还需转义`与{
+fn write_escaped(f: &mut fmt::Formatter<'_>, v: &[u8], interp: bool) -> fmt::Result {
+    for chunk in v.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '"' if !interp => f.write_str("\\\"")?,
+                '`' | '{' if interp => write!(f, "\\{}", c)?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_ascii_control() => write!(f, "\\{:03}", c as u32)?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        for b in chunk.invalid() {
+            write!(f, "\\{:03}", b)?;
+        }
+    }
+
+    Ok(())
+}
+
+// 将浮点数写为可以被重新扫描为浮点数的字面量
+//
+// @param f: 输出
+// @param v: 浮点数
+fn write_float(f: &mut fmt::Formatter<'_>, v: f64) -> fmt::Result {
+    if v.is_infinite() {
+        // 与string.format的%q一致
+        f.write_str("1e9999")
+    } else {
+        write!(f, "{:?}", v)
+    }
+}
+
+impl LexTokenSource<'_, '_> {
+    // 将Token的源码以字节形式写出；与Display不同，名字中非UTF-8的字节会被原样写出
+    //
+    // @param w: 写出的目标
+    pub fn write_source<W: io::Write + ?Sized>(&self, w: &mut W) -> io::Result<()> {
+        match self.token {
+            LexToken::Name(sym) => w.write_all(self.interner.resolve(*sym)),
+            _ => write!(w, "{}", self),
+        }
+    }
+}
+
+impl fmt::Display for LexTokenSource<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(text) = self.token.text() {
+            return f.write_str(text);
+        }
+
+        match self.token {
+            LexToken::Name(sym) => f.write_str(&self.interner.resolve_str(*sym)),
+            LexToken::Str(v) => {
+                f.write_str("\"")?;
+                write_escaped(f, v, false)?;
+                f.write_str("\"")
+            }
+            LexToken::ShortStr(sym) => {
+                f.write_str("\"")?;
+                write_escaped(f, self.interner.resolve(*sym), false)?;
+                f.write_str("\"")
+            }
+            LexToken::InterpBegin(v) | LexToken::InterpSimple(v) => {
+                f.write_str("`")?;
+                write_escaped(f, v, true)?;
+                f.write_str(if matches!(self.token, LexToken::InterpBegin(_)) {
+                    "{"
+                } else {
+                    "`"
+                })
+            }
+            LexToken::InterpMid(v) | LexToken::InterpEnd(v) => {
+                f.write_str("}")?;
+                write_escaped(f, v, true)?;
+                f.write_str(if matches!(self.token, LexToken::InterpMid(_)) {
+                    "{"
+                } else {
+                    "`"
+                })
+            }
+            LexToken::Number(v) => match v {
+                // 由十六进制整数回绕得到的负数只能以十六进制写出
+                LexNumberValue::Int(i) if *i < 0 => write!(f, "0x{:x}", i),
+                LexNumberValue::Int(i) => write!(f, "{}", i),
+                LexNumberValue::Float(v) => write_float(f, *v),
+                LexNumberValue::Int64(i) => write!(f, "{}LL", *i as u64),
+                LexNumberValue::UInt64(i) => write!(f, "{}ULL", i),
+                LexNumberValue::Imaginary(v) => {
+                    write_float(f, *v)?;
+                    f.write_str("i")
+                }
+                LexNumberValue::Invalid => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::{Dialect, LexStatus};
+
+    #[test]
+    fn lex_token_display() {
+        let mut interner = Interner::new();
+        let x = interner.intern("x");
+
+        assert_eq!(LexToken::Eof.to_string(), "<eof>");
+        assert_eq!(LexToken::Name(x).to_string(), "<name>");
+        assert_eq!(LexToken::ShortStr(x).to_string(), "<string>");
+        assert_eq!(LexToken::Str(Cow::Borrowed(b"x")).to_string(), "<string>");
+        assert_eq!(
+            LexToken::InterpBegin(Cow::Borrowed(b"x")).to_string(),
+            "<string>"
+        );
+        assert_eq!(
+            LexToken::Number(LexNumberValue::Int(1)).to_string(),
+            "<integer>"
+        );
+        assert_eq!(
+            LexToken::Number(LexNumberValue::Float(1.0)).to_string(),
+            "<number>"
+        );
+        assert_eq!(LexToken::Equal.to_string(), "'=='");
+        assert_eq!(LexToken::End.to_string(), "'end'");
+        assert_eq!(LexToken::Dots.to_string(), "'...'");
+        assert_eq!(format!("{:?}", LexToken::Name(x)), "Name(Symbol(0))");
+    }
+
+    fn round_trip(src: &str, dialect: Dialect) -> String {
+        let mut lex = LexStatus::new(src);
+        lex.set_dialect(dialect);
+        lex.setup();
+
+        let mut tokens = Vec::new();
+        loop {
+            let token = lex.scan().unwrap();
+            if token == LexToken::Eof {
+                break;
+            }
+            tokens.push(token);
+        }
+
+        let out = tokens
+            .iter()
+            .map(|t| t.source(lex.interner()).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        // 写出的源码重新扫描后得到相同的Token
+        {
+            let mut relex = LexStatus::new(&out);
+            relex.set_dialect(dialect);
+            relex.set_interner(lex.into_interner());
+            relex.setup();
+            for token in tokens {
+                assert_eq!(relex.scan().unwrap(), token);
+            }
+            assert_eq!(relex.scan().unwrap(), LexToken::Eof);
+        }
+
+        out
+    }
+
+    #[test]
+    fn lex_token_source() {
+        assert_eq!(
+            round_trip("local x<const> = {a.b, c:d(...)} // 2 ~= 3", Dialect::Lua54),
+            "local x < const > = { a . b , c : d ( ... ) } // 2 ~= 3"
+        );
+        assert_eq!(
+            round_trip(r#"x = 'a\"b\'c\\\0\1272\xff' .. [[é\r]]"#, Dialect::Lua54),
+            r#"x = "a\"b'c\\\000\1272\255" .. "é\\r""#
+        );
+        assert_eq!(
+            round_trip("0x10 0xffffffffffffffff 1.5 1e300 1e999 .5", Dialect::Lua54),
+            "16 0xffffffffffffffff 1.5 1e300 1e9999 0.5"
+        );
+        assert_eq!(
+            round_trip("1LL 0xffffffffffffffffLL 2ULL 3i 0.5i", Dialect::LuaJit),
+            "1LL 18446744073709551615LL 2ULL 3.0i 0.5i"
+        );
+        assert_eq!(
+            round_trip("`a\\`{x}\\{b{y}` `c` x ..= 1", Dialect::Luau),
+            "`a\\`{ x }\\{b{ y }` `c` x ..= 1.0"
+        );
+
+        let long = "x".repeat(50);
+        assert_eq!(
+            round_trip(&format!("'{}\\n'", long), Dialect::Lua54),
+            format!("\"{}\\n\"", long)
+        );
+    }
+
+    #[test]
+    fn lex_token_write_source() {
+        let src = &b"local x\xff = 'y\xff'"[..];
+        let mut lex = LexStatus::new(src);
+        lex.setup();
+
+        let mut out = Vec::new();
+        loop {
+            let token = lex.scan().unwrap();
+            if token == LexToken::Eof {
+                break;
+            }
+            if !out.is_empty() {
+                out.push(b' ');
+            }
+            token.source(lex.interner()).write_source(&mut out).unwrap();
+        }
+        assert_eq!(out, b"local x\xff = \"y\\255\"");
+
+        // 名字中的非UTF-8字节经Display写出时会被替换
+        let x = lex.interner_mut().intern(b"x\xff");
+        let token = LexToken::Name(x);
+        assert_eq!(token.source(lex.interner()).to_string(), "x\u{fffd}");
+    }
+}
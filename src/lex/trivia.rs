@@ -0,0 +1,67 @@
+use super::span::{Span, SpannedToken};
+
+// 空白及注释的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    Whitespace,
+    Newline,
+    LineComment,
+    LongComment,
+    Bom,
+    Shebang,
+}
+
+// 一段空白或注释，text为其在源码中的原始内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: Vec<u8>,
+    pub span: Span,
+}
+
+// 保留了原始内容及前后空白、注释的Token
+//
+// leading为Token之前的空白及注释，trailing为Token之后、同一行内的空白及注释(包括行尾的换行)；
+// 依次拼接每个Token的leading、text与trailing即可还原出完整的源码
+#[derive(Debug, PartialEq)]
+pub struct LosslessToken<'src_lt> {
+    pub leading: Vec<Trivia>,
+    pub token: SpannedToken<'src_lt>,
+    pub text: Vec<u8>,
+    pub trailing: Vec<Trivia>,
+}
+
+impl Trivia {
+    // 构造新的Trivia
+    //
+    // @param kind: 类型
+    // @param text: 原始内容
+    // @param span: 在源码中的区间
+    //
+    // @return: Trivia
+    pub fn new(kind: TriviaKind, text: Vec<u8>, span: Span) -> Self {
+        Trivia { kind, text, span }
+    }
+
+    // 是否为注释
+    //
+    // @return: 是否为注释
+    pub fn is_comment(&self) -> bool {
+        matches!(self.kind, TriviaKind::LineComment | TriviaKind::LongComment)
+    }
+}
+
+impl LosslessToken<'_> {
+    // 将Token及其前后的空白、注释按原样写入out
+    //
+    // @param out: 输出
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        for trivia in self.leading.iter() {
+            out.extend_from_slice(&trivia.text);
+        }
+        out.extend_from_slice(&self.text);
+        for trivia in self.trailing.iter() {
+            out.extend_from_slice(&trivia.text);
+        }
+    }
+}
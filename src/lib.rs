@@ -1,6 +1,7 @@
-mod lex;
-mod parse;
-mod toolbox;
+pub mod highlight;
+pub mod lex;
+pub mod parse;
+pub mod toolbox;
 
 #[cfg(test)]
 mod tests {
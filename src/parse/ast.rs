@@ -0,0 +1,284 @@
+use crate::lex::{LexNumberValue, Span, Symbol};
+use std::borrow::Cow;
+
+// 语句块
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Block<'src_lt> {
+    pub stats: Vec<Stat<'src_lt>>,
+}
+
+// 语句及其在源码中的区间
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stat<'src_lt> {
+    pub kind: StatKind<'src_lt>,
+    pub span: Span,
+}
+
+// 语句的种类
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatKind<'src_lt> {
+    // a, b.c, d[e] = f, g
+    Assign(Vec<Expr<'src_lt>>, Vec<Expr<'src_lt>>),
+    // Luau的复合赋值；目标为名字时直接改写为Assign，只有目标为索引时才保留，以免索引表达式被求值两次
+    CompoundAssign(BinOp, Box<Expr<'src_lt>>, Box<Expr<'src_lt>>),
+    // 函数调用语句，表达式一定是Call或MethodCall
+    Call(Expr<'src_lt>),
+    Local(Vec<LocalName>, Vec<Expr<'src_lt>>),
+    LocalFunction(Symbol, FuncBody<'src_lt>),
+    Function(FuncName, FuncBody<'src_lt>),
+    Do(Block<'src_lt>),
+    While(Expr<'src_lt>, Block<'src_lt>),
+    Repeat(Block<'src_lt>, Expr<'src_lt>),
+    // if及各个elseif的条件与语句块，以及可选的else语句块
+    If(Vec<(Expr<'src_lt>, Block<'src_lt>)>, Option<Block<'src_lt>>),
+    NumericFor(Box<NumericFor<'src_lt>>),
+    GenericFor(Box<GenericFor<'src_lt>>),
+    Return(Vec<Expr<'src_lt>>),
+    Break,
+    // Luau的continue
+    Continue,
+    Goto(Symbol),
+    Label(Symbol),
+}
+
+// local语句中的一个变量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalName {
+    pub name: Symbol,
+    pub attrib: Option<Attrib>,
+    pub span: Span,
+}
+
+// Lua 5.4的变量属性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attrib {
+    Const,
+    Close,
+}
+
+// function语句的函数名，如a.b.c:m
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuncName {
+    pub path: Vec<Symbol>,
+    pub method: Option<Symbol>,
+}
+
+// 数值for循环
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericFor<'src_lt> {
+    pub var: Symbol,
+    pub start: Expr<'src_lt>,
+    pub limit: Expr<'src_lt>,
+    pub step: Option<Expr<'src_lt>>,
+    pub body: Block<'src_lt>,
+}
+
+// 泛型for循环
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericFor<'src_lt> {
+    pub names: Vec<Symbol>,
+    pub exprs: Vec<Expr<'src_lt>>,
+    pub body: Block<'src_lt>,
+}
+
+// 函数的参数及函数体；方法的self不出现在params中
+#[derive(Debug, Clone, PartialEq)]
+pub struct FuncBody<'src_lt> {
+    pub params: Vec<Symbol>,
+    pub vararg: bool,
+    pub body: Block<'src_lt>,
+    pub span: Span,
+}
+
+// 表达式及其在源码中的区间
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expr<'src_lt> {
+    pub kind: ExprKind<'src_lt>,
+    pub span: Span,
+}
+
+// 表达式的种类
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExprKind<'src_lt> {
+    Nil,
+    True,
+    False,
+    Dots,
+    Number(LexNumberValue),
+    ShortStr(Symbol),
+    Str(Cow<'src_lt, [u8]>),
+    // Luau插值字符串，strings比exprs多一项，二者交替拼接；不含插值表达式的插值字符串直接作为字符串
+    Interp(Vec<Cow<'src_lt, [u8]>>, Vec<Expr<'src_lt>>),
+    Function(Box<FuncBody<'src_lt>>),
+    Table(Vec<Field<'src_lt>>),
+    Binary(BinOp, Box<Expr<'src_lt>>, Box<Expr<'src_lt>>),
+    Unary(UnOp, Box<Expr<'src_lt>>),
+    // 括号会将多个返回值截断为一个，因此需要保留
+    Paren(Box<Expr<'src_lt>>),
+    Name(Symbol),
+    // a.b
+    Field(Box<Expr<'src_lt>>, Symbol),
+    // a[b]
+    Index(Box<Expr<'src_lt>>, Box<Expr<'src_lt>>),
+    Call(Box<Expr<'src_lt>>, Vec<Expr<'src_lt>>),
+    MethodCall(Box<Expr<'src_lt>>, Symbol, Vec<Expr<'src_lt>>),
+    // Luau的if表达式，elseif改写为嵌套在else分支中的IfElse
+    IfElse(Box<Expr<'src_lt>>, Box<Expr<'src_lt>>, Box<Expr<'src_lt>>),
+}
+
+// 表构造器中的一项
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field<'src_lt> {
+    // name = value
+    Named(Symbol, Expr<'src_lt>),
+    // [key] = value
+    Index(Expr<'src_lt>, Expr<'src_lt>),
+    // value
+    Positional(Expr<'src_lt>),
+}
+
+// 二元运算符，名称与对应的LexToken一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    FloorDiv,
+    Mod,
+    Pow,
+    Concat,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greate,
+    GreateEqual,
+    And,
+    Or,
+    BitAnd,
+    BitOr,
+    BitXor,
+    ShiftLeft,
+    ShiftRight,
+}
+
+// 一元运算符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnOp {
+    Neg,
+    Not,
+    Len,
+    BitNot,
+}
+
+impl<'src_lt> Expr<'src_lt> {
+    // 构造新的Expr
+    //
+    // @param kind: 表达式的种类
+    // @param span: 在源码中的区间
+    //
+    // @return: Expr
+    pub fn new(kind: ExprKind<'src_lt>, span: Span) -> Self {
+        Expr { kind, span }
+    }
+
+    // 是否可以作为赋值的目标
+    //
+    // @return: 是否可以赋值
+    pub fn is_assignable(&self) -> bool {
+        matches!(
+            self.kind,
+            ExprKind::Name(_) | ExprKind::Field(..) | ExprKind::Index(..)
+        )
+    }
+
+    // 是否为函数调用
+    //
+    // @return: 是否为函数调用
+    pub fn is_call(&self) -> bool {
+        matches!(self.kind, ExprKind::Call(..) | ExprKind::MethodCall(..))
+    }
+}
+
+impl<'src_lt> Stat<'src_lt> {
+    // 构造新的Stat
+    //
+    // @param kind: 语句的种类
+    // @param span: 在源码中的区间
+    //
+    // @return: Stat
+    pub fn new(kind: StatKind<'src_lt>, span: Span) -> Self {
+        Stat { kind, span }
+    }
+}
+
+impl BinOp {
+    // 获取运算符在源码中的写法
+    //
+    // @return: 写法
+    pub fn text(&self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::FloorDiv => "//",
+            BinOp::Mod => "%",
+            BinOp::Pow => "^",
+            BinOp::Concat => "..",
+            BinOp::Equal => "==",
+            BinOp::NotEqual => "~=",
+            BinOp::Less => "<",
+            BinOp::LessEqual => "<=",
+            BinOp::Greate => ">",
+            BinOp::GreateEqual => ">=",
+            BinOp::And => "and",
+            BinOp::Or => "or",
+            BinOp::BitAnd => "&",
+            BinOp::BitOr => "|",
+            BinOp::BitXor => "~",
+            BinOp::ShiftLeft => "<<",
+            BinOp::ShiftRight => ">>",
+        }
+    }
+}
+
+impl UnOp {
+    // 获取运算符在源码中的写法
+    //
+    // @return: 写法
+    pub fn text(&self) -> &'static str {
+        match self {
+            UnOp::Neg => "-",
+            UnOp::Not => "not",
+            UnOp::Len => "#",
+            UnOp::BitNot => "~",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ast_expr_kind() {
+        let e = Expr::new(ExprKind::Nil, Span::default());
+        assert!(!e.is_assignable());
+        assert!(!e.is_call());
+
+        let index = Expr::new(
+            ExprKind::Index(Box::new(e.clone()), Box::new(e.clone())),
+            Span::default(),
+        );
+        assert!(index.is_assignable());
+
+        let call = Expr::new(ExprKind::Call(Box::new(index), vec![e]), Span::default());
+        assert!(call.is_call());
+        assert!(!call.is_assignable());
+
+        assert_eq!(BinOp::GreateEqual.text(), ">=");
+        assert_eq!(UnOp::Not.text(), "not");
+    }
+}
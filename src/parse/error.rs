@@ -0,0 +1,176 @@
+use crate::lex::{LexError, LexErrorKind, Span};
+use std::error::Error;
+use std::fmt;
+
+// 语法错误类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    // 词法错误
+    Lex(LexErrorKind),
+    // 缺少某个Token，内容为可以出现在此处的各个Token的写法，如'end'、<name>
+    Expected(Vec<String>),
+    // 缺少结束某个结构的Token：期望的Token、开始该结构的Token及其所在的行号
+    Unclosed(String, String, u32),
+    UnexpectedSymbol,
+    SyntaxError,
+    VarargOutsideFunction,
+    UnknownAttribute(String),
+    MultipleToBeClosed,
+    // 循环之外的break，内容为break所在的行号
+    BreakOutsideLoop(u32),
+    // 循环之外的continue，内容为continue所在的行号
+    ContinueOutsideLoop(u32),
+    // 重复定义的标签：标签名及先前定义所在的行号
+    RepeatedLabel(String, u32),
+    // 找不到goto跳转的标签：标签名及goto所在的行号
+    UndefinedLabel(String, u32),
+}
+
+// 语法错误，包含出错位置、出错处的Token及其区间
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: u32,
+    pub column: u32,
+    pub near: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    // 构造新的ParseError，出错位置为区间的起始位置
+    //
+    // @param kind: 错误类型
+    // @param span: 出错处的Token在源码中的区间
+    // @param near: 出错处的Token
+    //
+    // @return: ParseError
+    pub fn new(kind: ParseErrorKind, span: Span, near: String) -> Self {
+        ParseError {
+            kind,
+            line: span.start.line,
+            column: span.start.column,
+            near,
+            span,
+        }
+    }
+
+    // 由词法错误构造ParseError，出错位置沿用词法错误的位置
+    //
+    // @param e: 词法错误
+    // @param span: 出错的一段源码的区间
+    //
+    // @return: ParseError
+    pub fn from_lex(e: LexError, span: Span) -> Self {
+        ParseError {
+            kind: ParseErrorKind::Lex(e.kind),
+            line: e.line,
+            column: e.column,
+            near: e.near,
+            span,
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::Lex(e) => e.fmt(f),
+            ParseErrorKind::Expected(tokens) => {
+                // 'a'、'a' or 'b'、'a', 'b' or 'c'
+                for (i, token) in tokens.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(if i + 1 == tokens.len() { " or " } else { ", " })?;
+                    }
+                    f.write_str(token)?;
+                }
+                f.write_str(" expected")
+            }
+            ParseErrorKind::Unclosed(what, who, line) => {
+                write!(f, "{} expected (to close {} at line {})", what, who, line)
+            }
+            ParseErrorKind::UnexpectedSymbol => f.write_str("unexpected symbol"),
+            ParseErrorKind::SyntaxError => f.write_str("syntax error"),
+            ParseErrorKind::VarargOutsideFunction => {
+                f.write_str("cannot use '...' outside a vararg function")
+            }
+            ParseErrorKind::UnknownAttribute(name) => write!(f, "unknown attribute '{}'", name),
+            ParseErrorKind::MultipleToBeClosed => {
+                f.write_str("multiple to-be-closed variables in local list")
+            }
+            ParseErrorKind::BreakOutsideLoop(line) => {
+                write!(f, "break outside a loop at line {}", line)
+            }
+            ParseErrorKind::ContinueOutsideLoop(line) => {
+                write!(f, "continue outside a loop at line {}", line)
+            }
+            ParseErrorKind::RepeatedLabel(name, line) => {
+                write!(f, "label '{}' already defined on line {}", name, line)
+            }
+            ParseErrorKind::UndefinedLabel(name, line) => {
+                write!(f, "no visible label '{}' for <goto> at line {}", name, line)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} near '{}'",
+            self.line, self.column, self.kind, self.near
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::Position;
+
+    fn span(line: u32, column: u32) -> Span {
+        let pos = Position::new(0, line, column);
+        Span::new(pos, pos)
+    }
+
+    #[test]
+    fn parse_error_display() {
+        let expected = |tokens: &[&str]| {
+            let tokens = tokens.iter().map(|t| t.to_string()).collect();
+            ParseError::new(
+                ParseErrorKind::Expected(tokens),
+                span(3, 1),
+                String::from("x"),
+            )
+        };
+        assert_eq!(
+            expected(&["'end'"]).to_string(),
+            "3:1: 'end' expected near 'x'"
+        );
+        assert_eq!(
+            expected(&["'='", "'in'"]).to_string(),
+            "3:1: '=' or 'in' expected near 'x'"
+        );
+        assert_eq!(
+            expected(&["','", "';'", "'}'"]).to_string(),
+            "3:1: ',', ';' or '}' expected near 'x'"
+        );
+
+        let e = ParseError::new(
+            ParseErrorKind::Unclosed(String::from("'end'"), String::from("'function'"), 12),
+            span(20, 1),
+            String::from("<eof>"),
+        );
+        assert_eq!(
+            e.to_string(),
+            "20:1: 'end' expected (to close 'function' at line 12) near '<eof>'"
+        );
+
+        let lex = LexError::new(LexErrorKind::UnfinishedString, 1, 5, String::from("'a"));
+        let e = ParseError::from_lex(lex, span(1, 3));
+        assert_eq!(e.to_string(), "1:5: unfinished string near ''a'");
+        assert_eq!(e.span.start.column, 3);
+    }
+}
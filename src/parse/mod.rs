@@ -1 +1,14 @@
-mod parser;
+pub mod ast;
+pub mod error;
+pub mod parser;
+pub mod precedence;
+pub mod visit;
+
+pub use ast::{
+    Attrib, BinOp, Block, Expr, ExprKind, Field, FuncBody, FuncName, GenericFor, LocalName,
+    NumericFor, Stat, StatKind, UnOp,
+};
+pub use error::{ParseError, ParseErrorKind};
+pub use parser::Parser;
+pub use precedence::{Assoc, UNARY_PRIORITY};
+pub use visit::{Visitor, VisitorMut};
@@ -1,9 +1,1725 @@
-use crate::lex::LexToken;
+use super::ast::{
+    Attrib, BinOp, Block, Expr, ExprKind, Field, FuncBody, FuncName, GenericFor, LocalName,
+    NumericFor, Stat, StatKind, UnOp,
+};
+use super::error::{ParseError, ParseErrorKind};
+use super::precedence::UNARY_PRIORITY;
+use crate::lex::{
+    Dialect, Interner, LexStatus, LexToken, Position, Span, SpannedToken, Symbol, TokenStream,
+    MAX_SHORT_LEN,
+};
+use std::borrow::Cow;
+use std::mem;
 
-struct Parser {
-    token: Option<LexToken>,
+// 递归下降的语法分析器，结构与lparser.c一致
+pub struct Parser<'src_lt> {
+    stream: TokenStream<'src_lt>,
+    src: &'src_lt [u8],
+    token: SpannedToken<'src_lt>,
+    prev_end: Position,
+    // 各层函数的分析状态
+    funcs: Vec<FuncState>,
+    // Luau中作为上下文关键字的continue
+    continue_sym: Option<Symbol>,
+    recovery: bool,
+    diagnostics: Vec<ParseError>,
 }
 
-impl Parser {
-    fn parse_chunk(&mut self) {}
+// 函数的分析状态，对应lparser.c中的FuncState
+#[derive(Default)]
+struct FuncState {
+    // 是否接受可变参数
+    vararg: bool,
+    // 所在的循环层数，为0时不能使用break及continue
+    loops: u32,
+    // 各层语句块中已定义的标签及其所在的行号
+    labels: Vec<Vec<(Symbol, u32)>>,
+    // 尚未找到标签的goto
+    gotos: Vec<PendingGoto>,
+}
+
+// 尚未找到标签的goto
+struct PendingGoto {
+    name: Symbol,
+    // 可见的语句块层数，只有这些语句块中的标签对该goto可见
+    level: usize,
+    // 直到函数结束仍未找到标签时报告的错误
+    error: ParseError,
+}
+
+impl<'src_lt> Parser<'src_lt> {
+    // 构造新的Parser，使用默认的语言方言
+    //
+    // @param src: 传入的Lua源码
+    //
+    // @return: Parser
+    pub fn new<S: AsRef<[u8]> + ?Sized>(src: &'src_lt S) -> Self {
+        Parser::with_dialect(src, Dialect::default())
+    }
+
+    // 构造新的Parser
+    //
+    // @param src: 传入的Lua源码
+    // @param dialect: 语言方言
+    //
+    // @return: Parser
+    pub fn with_dialect<S: AsRef<[u8]> + ?Sized>(src: &'src_lt S, dialect: Dialect) -> Self {
+        let src = src.as_ref();
+        let mut lex = LexStatus::new(src);
+        lex.set_dialect(dialect);
+
+        let continue_sym = if dialect.has_continue() {
+            Some(lex.interner_mut().intern("continue"))
+        } else {
+            None
+        };
+
+        Parser {
+            stream: TokenStream::from_status(lex),
+            src,
+            token: SpannedToken::new(LexToken::Eof, Span::default()),
+            prev_end: Position::default(),
+            funcs: Vec::new(),
+            continue_sym,
+            recovery: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    // 设置是否开启恢复模式：开启后遇到错误不再中止分析，而是记录该错误，
+    // 跳到下一条语句的开头继续分析，词法错误同样被记录并跳过。
+    // 恢复模式下parse_chunk总是返回尽可能完整的AST，全部错误由diagnostics获取
+    //
+    // @param recovery: 是否开启
+    pub fn set_recovery(&mut self, recovery: bool) {
+        self.recovery = recovery;
+        self.stream.status_mut().set_recovery(recovery);
+    }
+
+    // 获取恢复模式下记录的全部错误，按出现的顺序排列
+    //
+    // @return: 错误列表
+    pub fn diagnostics(&self) -> &[ParseError] {
+        &self.diagnostics
+    }
+
+    // 取出恢复模式下记录的全部错误
+    //
+    // @return: 错误列表
+    pub fn take_diagnostics(&mut self) -> Vec<ParseError> {
+        mem::take(&mut self.diagnostics)
+    }
+
+    // 替换字符串驻留表，用于在多个Parser及之后的编译过程间共享同一张表，须在parse_chunk之前调用
+    //
+    // @param interner: 字符串驻留表
+    //
+    // @return: 原先的字符串驻留表
+    pub fn set_interner(&mut self, interner: Interner) -> Interner {
+        let lex = self.stream.status_mut();
+        let old = lex.set_interner(interner);
+        // continue的编号随驻留表变化
+        if self.continue_sym.is_some() {
+            self.continue_sym = Some(lex.interner_mut().intern("continue"));
+        }
+
+        old
+    }
+
+    // 获取字符串驻留表，AST中的名字及短字符串均来自该表
+    //
+    // @return: 字符串驻留表
+    pub fn interner(&self) -> &Interner {
+        self.stream.status().interner()
+    }
+
+    // 取出字符串驻留表
+    //
+    // @return: 字符串驻留表
+    pub fn into_interner(self) -> Interner {
+        self.stream.into_status().into_interner()
+    }
+
+    // 分析整个源码
+    //
+    // @return: 主函数的语句块
+    pub fn parse_chunk(&mut self) -> Result<Block<'src_lt>, ParseError> {
+        self.next()?;
+
+        // 主函数总是接受可变参数
+        self.open_func(true);
+        let mut block = self.block()?;
+        while self.recovery && self.token.token != LexToken::Eof {
+            // 多余的end、until或return之后的语句，跳过后继续分析
+            let e = self.error_expected(&[LexToken::Eof]);
+            self.recover(e, self.token.span.start, 1)?;
+            block.stats.extend(self.block()?.stats);
+        }
+        self.check(LexToken::Eof)?;
+        self.close_func()?;
+
+        Ok(block)
+    }
+
+    // 扫描下一个Token；恢复模式下词法错误被记录并跳过
+    //
+    // @return: Token
+    fn scan(&mut self) -> Result<SpannedToken<'src_lt>, ParseError> {
+        self.skip_errors();
+        match self.stream.next() {
+            Some(Ok(token)) => Ok(token),
+            Some(Err(e)) => Err(ParseError::from_lex(e, self.stream.status().span())),
+            // Token流在Eof之后结束，此后一直停留在Eof
+            None => Ok(SpannedToken::new(LexToken::Eof, self.token.span)),
+        }
+    }
+
+    // 跳过Token流中紧接着的Error，将其记录为错误；Error只在恢复模式下出现
+    fn skip_errors(&mut self) {
+        while let Some(Ok(SpannedToken {
+            token: LexToken::Error(_),
+            ..
+        })) = self.stream.peek()
+        {
+            if let Some(Ok(SpannedToken {
+                token: LexToken::Error(e),
+                span,
+            })) = self.stream.next()
+            {
+                self.diagnostics.push(ParseError::from_lex(*e, span));
+            }
+        }
+    }
+
+    // 读取下一个Token
+    fn next(&mut self) -> Result<(), ParseError> {
+        self.prev_end = self.token.span.end;
+        self.token = self.scan()?;
+
+        Ok(())
+    }
+
+    // 向前查看下一个Token
+    //
+    // @return: 下一个Token
+    fn peek(&mut self) -> Result<&LexToken<'src_lt>, ParseError> {
+        self.skip_errors();
+        if let Some(Err(e)) = self.stream.peek() {
+            let e = e.clone();
+            return Err(ParseError::from_lex(e, self.stream.status().span()));
+        }
+
+        match self.stream.peek() {
+            Some(Ok(token)) => Ok(&token.token),
+            _ => Ok(&LexToken::Eof),
+        }
+    }
+
+    // 取出当前Token并读取下一个Token
+    //
+    // @return: 当前Token
+    fn take(&mut self) -> Result<LexToken<'src_lt>, ParseError> {
+        let token = mem::replace(&mut self.token.token, LexToken::Eof);
+        self.next()?;
+
+        Ok(token)
+    }
+
+    // 当前Token为token时读取下一个Token
+    //
+    // @param token: 期望的Token
+    //
+    // @return: 当前Token是否为token
+    fn test_next(&mut self, token: &LexToken) -> Result<bool, ParseError> {
+        if self.token.token == *token {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    // 检查当前Token是否为token
+    //
+    // @param token: 期望的Token
+    fn check(&self, token: LexToken) -> Result<(), ParseError> {
+        if self.token.token == token {
+            Ok(())
+        } else {
+            Err(self.error_expected(&[token]))
+        }
+    }
+
+    // 检查当前Token是否为结束某个结构的what，并读取下一个Token。
+    // 该结构不在同一行开始时，错误信息中会指出开始该结构的Token及其行号
+    //
+    // @param what: 期望的Token
+    // @param who: 开始该结构的Token
+    // @param line: 开始该结构的行号
+    fn check_match(&mut self, what: LexToken, who: LexToken, line: u32) -> Result<(), ParseError> {
+        if self.token.token == what {
+            return self.next();
+        }
+
+        if line == self.token.span.start.line {
+            Err(self.error_expected(&[what]))
+        } else {
+            let kind = ParseErrorKind::Unclosed(what.to_string(), who.to_string(), line);
+            Err(self.error(kind))
+        }
+    }
+
+    // 检查当前Token是否为token，并读取下一个Token
+    //
+    // @param token: 期望的Token
+    fn check_next(&mut self, token: LexToken) -> Result<(), ParseError> {
+        self.check(token)?;
+        self.next()
+    }
+
+    // 读取一个名字
+    //
+    // @return: 名字
+    fn check_name(&mut self) -> Result<Symbol, ParseError> {
+        match self.token.token {
+            LexToken::Name(sym) => {
+                self.next()?;
+                Ok(sym)
+            }
+            _ => Err(self.error_expected(&["<name>"])),
+        }
+    }
+
+    // 在当前Token处构造一个语法错误
+    //
+    // @param kind: 错误类型
+    //
+    // @return: 语法错误
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        match self.token.token {
+            LexToken::Eof => ParseError::new(kind, self.token.span, String::from("<eof>")),
+            _ => self.error_at(kind, self.token.span),
+        }
+    }
+
+    // 在一段源码处构造一个语法错误
+    //
+    // @param kind: 错误类型
+    // @param span: 出错的源码区间
+    //
+    // @return: 语法错误
+    fn error_at(&self, kind: ParseErrorKind, span: Span) -> ParseError {
+        let near = String::from_utf8_lossy(&self.src[span.start.offset..span.end.offset]);
+        ParseError::new(kind, span, near.into_owned())
+    }
+
+    // 在当前Token处构造一个缺少Token的语法错误
+    //
+    // @param tokens: 可以出现在此处的各个Token的写法
+    //
+    // @return: 语法错误
+    fn error_expected<T: ToString>(&self, tokens: &[T]) -> ParseError {
+        let tokens = tokens.iter().map(|t| t.to_string()).collect();
+        self.error(ParseErrorKind::Expected(tokens))
+    }
+
+    // 从start到上一个Token结束的区间
+    //
+    // @param start: 起始位置
+    //
+    // @return: 区间
+    fn span_from(&self, start: Position) -> Span {
+        Span::new(start, self.prev_end)
+    }
+
+    // 当前Token是否结束一个语句块
+    //
+    // @return: 是否结束
+    fn block_follow(&self) -> bool {
+        matches!(
+            self.token.token,
+            LexToken::Else | LexToken::ElseIf | LexToken::End | LexToken::Eof | LexToken::Until
+        )
+    }
+
+    // 当前正在分析的函数
+    //
+    // @return: 函数的分析状态
+    fn func(&self) -> &FuncState {
+        self.funcs.last().unwrap()
+    }
+
+    // 当前正在分析的函数
+    //
+    // @return: 可修改的函数的分析状态
+    fn func_mut(&mut self) -> &mut FuncState {
+        self.funcs.last_mut().unwrap()
+    }
+
+    // 开始分析一个函数
+    //
+    // @param vararg: 函数是否接受可变参数
+    fn open_func(&mut self, vararg: bool) {
+        self.funcs.push(FuncState {
+            vararg,
+            ..FuncState::default()
+        });
+    }
+
+    // 结束分析一个函数，此时仍未找到标签的goto均为错误
+    fn close_func(&mut self) -> Result<(), ParseError> {
+        let fs = self.funcs.pop().unwrap();
+        for goto in fs.gotos {
+            if !self.recovery {
+                return Err(goto.error);
+            }
+            self.diagnostics.push(goto.error);
+        }
+
+        Ok(())
+    }
+
+    // 离开一个语句块：其中的标签不再可见，尚未找到标签的goto改为在外层语句块中查找
+    fn leave_block(&mut self) {
+        let fs = self.func_mut();
+        fs.labels.pop();
+        let level = fs.labels.len();
+        for goto in &mut fs.gotos {
+            goto.level = goto.level.min(level);
+        }
+    }
+
+    // 检查当前函数中可见的标签是否已有同名的标签
+    //
+    // @param name: 标签名
+    fn check_repeated(&self, name: Symbol) -> Result<(), ParseError> {
+        match self
+            .func()
+            .labels
+            .iter()
+            .flatten()
+            .find(|(sym, _)| *sym == name)
+        {
+            Some(&(_, line)) => {
+                let name = self.interner().resolve_str(name).into_owned();
+                Err(self.error(ParseErrorKind::RepeatedLabel(name, line)))
+            }
+            None => Ok(()),
+        }
+    }
+
+    // 在当前语句块中定义一个标签，并解析此前跳转到该标签的goto
+    //
+    // @param name: 标签名
+    // @param line: 标签所在的行号
+    fn create_label(&mut self, name: Symbol, line: u32) {
+        let fs = self.func_mut();
+        let level = fs.labels.len();
+        fs.labels.last_mut().unwrap().push((name, line));
+        fs.gotos
+            .retain(|goto| goto.name != name || goto.level < level);
+    }
+
+    // 查找goto跳转的标签，跳转到之后的标签的goto在定义该标签时解析；
+    // 当前Token应为goto的标签名，标签不存在时的错误在此处构造
+    //
+    // @param name: 标签名
+    // @param line: goto所在的行号
+    fn goto(&mut self, name: Symbol, line: u32) {
+        let fs = self.func();
+        if fs.labels.iter().flatten().any(|(sym, _)| *sym == name) {
+            return;
+        }
+
+        let level = fs.labels.len();
+        let label = self.interner().resolve_str(name).into_owned();
+        let error = self.error(ParseErrorKind::UndefinedLabel(label, line));
+        self.func_mut()
+            .gotos
+            .push(PendingGoto { name, level, error });
+    }
+
+    // 分析循环体，循环体中可以使用break
+    fn loop_block(&mut self) -> Result<Block<'src_lt>, ParseError> {
+        self.func_mut().loops += 1;
+        let block = self.block()?;
+        self.func_mut().loops -= 1;
+
+        Ok(block)
+    }
+
+    // block -> {stat} [retstat]
+    fn block(&mut self) -> Result<Block<'src_lt>, ParseError> {
+        let mut stats = Vec::new();
+        let depth = self.funcs.len();
+        self.func_mut().labels.push(Vec::new());
+
+        while !self.block_follow() {
+            let start = self.token.span.start;
+            if self.token.token == LexToken::Return {
+                // return必须是语句块的最后一条语句
+                match self.retstat() {
+                    Ok(stat) => stats.push(stat),
+                    Err(e) => {
+                        self.recover(e, start, depth)?;
+                        continue;
+                    }
+                }
+                break;
+            }
+            match self.statement() {
+                Ok(Some(stat)) => stats.push(stat),
+                Ok(None) => {}
+                Err(e) => self.recover(e, start, depth)?,
+            }
+        }
+        self.leave_block();
+
+        Ok(Block { stats })
+    }
+
+    // 恢复模式下记录错误，并跳到下一条语句的开头继续分析；非恢复模式下直接返回错误
+    //
+    // @param e: 语法错误
+    // @param start: 出错语句的起始位置
+    // @param depth: 出错语句所在语句块的函数嵌套深度
+    fn recover(&mut self, e: ParseError, start: Position, depth: usize) -> Result<(), ParseError> {
+        // 读取失败等无法恢复的错误
+        if !self.recovery || matches!(e.kind, ParseErrorKind::Lex(_)) {
+            return Err(e);
+        }
+        // 紧接在词法错误之后出错时，该语法错误由词法错误引起，不再重复报告
+        let cascade = self.diagnostics.last().is_some_and(|last| {
+            matches!(last.kind, ParseErrorKind::Lex(_))
+                && (last.line, last.column) == (e.line, e.column)
+        });
+        if !cascade {
+            self.diagnostics.push(e);
+        }
+        self.funcs.truncate(depth);
+
+        // 至少跳过一个Token，以免在同一位置反复出错
+        if self.token.span.start.offset == start.offset {
+            self.next()?;
+        }
+        while !self.block_follow() && !self.stat_start() {
+            self.next()?;
+        }
+
+        Ok(())
+    }
+
+    // 当前Token是否可以作为恢复分析时新语句的开头：语句关键字，或者位于新一行开头的名字
+    //
+    // @return: 是否为语句的开头
+    fn stat_start(&self) -> bool {
+        match self.token.token {
+            LexToken::If
+            | LexToken::While
+            | LexToken::Do
+            | LexToken::For
+            | LexToken::Repeat
+            | LexToken::Function
+            | LexToken::Local
+            | LexToken::Label
+            | LexToken::Return
+            | LexToken::Break
+            | LexToken::Goto
+            | LexToken::Semicolon => true,
+            LexToken::Name(_) => self.token.span.start.line > self.prev_end.line,
+            _ => false,
+        }
+    }
+
+    // retstat -> RETURN [explist] [';']
+    fn retstat(&mut self) -> Result<Stat<'src_lt>, ParseError> {
+        let start = self.token.span.start;
+        self.next()?;
+
+        let exprs = if self.block_follow() || self.token.token == LexToken::Semicolon {
+            Vec::new()
+        } else {
+            self.explist()?
+        };
+        self.test_next(&LexToken::Semicolon)?;
+
+        Ok(Stat::new(StatKind::Return(exprs), self.span_from(start)))
+    }
+
+    // 分析一条语句，空语句返回None
+    //
+    // @return: 语句
+    fn statement(&mut self) -> Result<Option<Stat<'src_lt>>, ParseError> {
+        let start = self.token.span.start;
+
+        let kind = match self.token.token {
+            LexToken::Semicolon => {
+                self.next()?;
+                return Ok(None);
+            }
+            LexToken::If => self.ifstat(start.line)?,
+            LexToken::While => {
+                self.next()?;
+                let cond = self.expr()?;
+                self.check_next(LexToken::Do)?;
+                let body = self.loop_block()?;
+                self.check_match(LexToken::End, LexToken::While, start.line)?;
+                StatKind::While(cond, body)
+            }
+            LexToken::Do => {
+                self.next()?;
+                let body = self.block()?;
+                self.check_match(LexToken::End, LexToken::Do, start.line)?;
+                StatKind::Do(body)
+            }
+            LexToken::For => self.forstat(start.line)?,
+            LexToken::Repeat => {
+                self.next()?;
+                let body = self.loop_block()?;
+                self.check_match(LexToken::Until, LexToken::Repeat, start.line)?;
+                let cond = self.expr()?;
+                StatKind::Repeat(body, cond)
+            }
+            LexToken::Function => self.funcstat(start)?,
+            LexToken::Local => {
+                self.next()?;
+                if self.test_next(&LexToken::Function)? {
+                    let name = self.check_name()?;
+                    StatKind::LocalFunction(name, self.body(start)?)
+                } else {
+                    self.localstat()?
+                }
+            }
+            LexToken::Label => {
+                self.next()?;
+                if let LexToken::Name(name) = self.token.token {
+                    self.check_repeated(name)?;
+                }
+                let name = self.check_name()?;
+                self.check_next(LexToken::Label)?;
+                self.create_label(name, start.line);
+                StatKind::Label(name)
+            }
+            LexToken::Break => {
+                if self.func().loops == 0 {
+                    return Err(self.error(ParseErrorKind::BreakOutsideLoop(start.line)));
+                }
+                self.next()?;
+                StatKind::Break
+            }
+            LexToken::Goto => {
+                self.next()?;
+                if let LexToken::Name(name) = self.token.token {
+                    self.goto(name, start.line);
+                }
+                StatKind::Goto(self.check_name()?)
+            }
+            _ => self.exprstat()?,
+        };
+
+        Ok(Some(Stat::new(kind, self.span_from(start))))
+    }
+
+    // ifstat -> IF cond THEN block {ELSEIF cond THEN block} [ELSE block] END
+    //
+    // @param line: if关键字所在的行号
+    fn ifstat(&mut self, line: u32) -> Result<StatKind<'src_lt>, ParseError> {
+        let mut conds = Vec::new();
+
+        loop {
+            // 跳过if或elseif
+            self.next()?;
+            let cond = self.expr()?;
+            self.check_next(LexToken::Then)?;
+            conds.push((cond, self.block()?));
+
+            if self.token.token != LexToken::ElseIf {
+                break;
+            }
+        }
+
+        let else_block = if self.test_next(&LexToken::Else)? {
+            Some(self.block()?)
+        } else {
+            None
+        };
+        self.check_match(LexToken::End, LexToken::If, line)?;
+
+        Ok(StatKind::If(conds, else_block))
+    }
+
+    // forstat -> FOR (fornum | forlist) END
+    //
+    // @param line: for关键字所在的行号
+    fn forstat(&mut self, line: u32) -> Result<StatKind<'src_lt>, ParseError> {
+        self.next()?;
+        let var = self.check_name()?;
+
+        let kind = match self.token.token {
+            LexToken::Assign => {
+                self.next()?;
+                let start = self.expr()?;
+                self.check_next(LexToken::Comma)?;
+                let limit = self.expr()?;
+                let step = if self.test_next(&LexToken::Comma)? {
+                    Some(self.expr()?)
+                } else {
+                    None
+                };
+                self.check_next(LexToken::Do)?;
+                let body = self.loop_block()?;
+
+                StatKind::NumericFor(Box::new(NumericFor {
+                    var,
+                    start,
+                    limit,
+                    step,
+                    body,
+                }))
+            }
+            LexToken::Comma | LexToken::In => {
+                let mut names = vec![var];
+                while self.test_next(&LexToken::Comma)? {
+                    names.push(self.check_name()?);
+                }
+                self.check_next(LexToken::In)?;
+                let exprs = self.explist()?;
+                self.check_next(LexToken::Do)?;
+                let body = self.loop_block()?;
+
+                StatKind::GenericFor(Box::new(GenericFor { names, exprs, body }))
+            }
+            _ => {
+                return Err(self.error_expected(&["'='", "'in'"]));
+            }
+        };
+        self.check_match(LexToken::End, LexToken::For, line)?;
+
+        Ok(kind)
+    }
+
+    // funcstat -> FUNCTION funcname body
+    //
+    // @param start: function关键字的位置
+    fn funcstat(&mut self, start: Position) -> Result<StatKind<'src_lt>, ParseError> {
+        self.next()?;
+
+        // funcname -> NAME {'.' NAME} [':' NAME]
+        let mut path = vec![self.check_name()?];
+        while self.test_next(&LexToken::Dot)? {
+            path.push(self.check_name()?);
+        }
+        let method = if self.test_next(&LexToken::MethodCall)? {
+            Some(self.check_name()?)
+        } else {
+            None
+        };
+
+        let body = self.body(start)?;
+        Ok(StatKind::Function(FuncName { path, method }, body))
+    }
+
+    // localstat -> LOCAL NAME attrib {',' NAME attrib} ['=' explist]
+    fn localstat(&mut self) -> Result<StatKind<'src_lt>, ParseError> {
+        let mut names = Vec::new();
+        let mut close = false;
+
+        loop {
+            let start = self.token.span.start;
+            let name = self.check_name()?;
+            let attrib = self.attrib()?;
+            if attrib == Some(Attrib::Close) {
+                if close {
+                    return Err(self.error(ParseErrorKind::MultipleToBeClosed));
+                }
+                close = true;
+            }
+            names.push(LocalName {
+                name,
+                attrib,
+                span: self.span_from(start),
+            });
+
+            if !self.test_next(&LexToken::Comma)? {
+                break;
+            }
+        }
+
+        let exprs = if self.test_next(&LexToken::Assign)? {
+            self.explist()?
+        } else {
+            Vec::new()
+        };
+
+        Ok(StatKind::Local(names, exprs))
+    }
+
+    // attrib -> ['<' NAME '>']
+    //
+    // @return: 变量属性
+    fn attrib(&mut self) -> Result<Option<Attrib>, ParseError> {
+        if !self.stream.status().dialect().has_local_attributes()
+            || self.token.token != LexToken::Less
+        {
+            return Ok(None);
+        }
+
+        self.next()?;
+        let name = self.check_name()?;
+        self.check_next(LexToken::Greate)?;
+
+        match self.interner().resolve(name) {
+            b"const" => Ok(Some(Attrib::Const)),
+            b"close" => Ok(Some(Attrib::Close)),
+            name => {
+                let name = String::from_utf8_lossy(name).into_owned();
+                Err(self.error(ParseErrorKind::UnknownAttribute(name)))
+            }
+        }
+    }
+
+    // exprstat -> func | assignment
+    fn exprstat(&mut self) -> Result<StatKind<'src_lt>, ParseError> {
+        let e = self.suffixedexp()?;
+
+        if matches!(self.token.token, LexToken::Assign | LexToken::Comma) {
+            return self.restassign(e);
+        }
+        if let Some(op) = self.compound_op() {
+            return self.compound_assign(op, e);
+        }
+        if e.is_call() {
+            return Ok(StatKind::Call(e));
+        }
+
+        // 既不是函数调用也不是赋值时，Luau中单独的continue为continue语句，与break一样只能用在循环中
+        match e.kind {
+            ExprKind::Name(sym) if Some(sym) == self.continue_sym => {
+                if self.func().loops == 0 {
+                    let kind = ParseErrorKind::ContinueOutsideLoop(e.span.start.line);
+                    return Err(self.error_at(kind, e.span));
+                }
+                Ok(StatKind::Continue)
+            }
+            _ => Err(self.error(ParseErrorKind::SyntaxError)),
+        }
+    }
+
+    // restassign -> {',' suffixedexp} '=' explist
+    //
+    // @param first: 第一个赋值目标
+    fn restassign(&mut self, first: Expr<'src_lt>) -> Result<StatKind<'src_lt>, ParseError> {
+        let mut targets = vec![first];
+
+        loop {
+            if !targets.last().unwrap().is_assignable() {
+                return Err(self.error(ParseErrorKind::SyntaxError));
+            }
+            if !self.test_next(&LexToken::Comma)? {
+                break;
+            }
+            targets.push(self.suffixedexp()?);
+        }
+        self.check_next(LexToken::Assign)?;
+        let exprs = self.explist()?;
+
+        Ok(StatKind::Assign(targets, exprs))
+    }
+
+    // 获取当前Token对应的复合赋值运算符
+    //
+    // @return: 二元运算符
+    fn compound_op(&self) -> Option<BinOp> {
+        Some(match self.token.token {
+            LexToken::AddAssign => BinOp::Add,
+            LexToken::SubAssign => BinOp::Sub,
+            LexToken::MulAssign => BinOp::Mul,
+            LexToken::DivAssign => BinOp::Div,
+            LexToken::FloorDivAssign => BinOp::FloorDiv,
+            LexToken::ModAssign => BinOp::Mod,
+            LexToken::PowAssign => BinOp::Pow,
+            LexToken::ConcatAssign => BinOp::Concat,
+            _ => return None,
+        })
+    }
+
+    // compoundassign -> suffixedexp compoundop expr
+    //
+    // @param op: 复合赋值对应的二元运算符
+    // @param target: 赋值目标
+    fn compound_assign(
+        &mut self,
+        op: BinOp,
+        target: Expr<'src_lt>,
+    ) -> Result<StatKind<'src_lt>, ParseError> {
+        if !target.is_assignable() {
+            return Err(self.error(ParseErrorKind::SyntaxError));
+        }
+        self.next()?;
+        let value = self.expr()?;
+
+        if let ExprKind::Name(_) = target.kind {
+            // a += b改写为a = a + b，a + b的区间从a开始到b结束，不包含整条语句
+            let span = Span::new(target.span.start, value.span.end);
+            let value = Expr::new(
+                ExprKind::Binary(op, Box::new(target.clone()), Box::new(value)),
+                span,
+            );
+            return Ok(StatKind::Assign(vec![target], vec![value]));
+        }
+
+        Ok(StatKind::CompoundAssign(
+            op,
+            Box::new(target),
+            Box::new(value),
+        ))
+    }
+
+    // body -> '(' parlist ')' block END
+    //
+    // @param start: 函数定义的起始位置
+    fn body(&mut self, start: Position) -> Result<FuncBody<'src_lt>, ParseError> {
+        self.check_next(LexToken::RoundBracketLeft)?;
+
+        // parlist -> [{NAME ','} (NAME | '...')]
+        let mut params = Vec::new();
+        let mut vararg = false;
+        if self.token.token != LexToken::RoundBracketRight {
+            loop {
+                match self.token.token {
+                    LexToken::Name(sym) => {
+                        self.next()?;
+                        params.push(sym);
+                    }
+                    LexToken::Dots => {
+                        self.next()?;
+                        vararg = true;
+                    }
+                    _ => return Err(self.error_expected(&["<name>"])),
+                }
+                if vararg || !self.test_next(&LexToken::Comma)? {
+                    break;
+                }
+            }
+        }
+        self.check_next(LexToken::RoundBracketRight)?;
+
+        self.open_func(vararg);
+        let body = self.block()?;
+        self.check_match(LexToken::End, LexToken::Function, start.line)?;
+        self.close_func()?;
+
+        Ok(FuncBody {
+            params,
+            vararg,
+            body,
+            span: self.span_from(start),
+        })
+    }
+
+    // explist -> expr {',' expr}
+    fn explist(&mut self) -> Result<Vec<Expr<'src_lt>>, ParseError> {
+        let mut exprs = vec![self.expr()?];
+        while self.test_next(&LexToken::Comma)? {
+            exprs.push(self.expr()?);
+        }
+
+        Ok(exprs)
+    }
+
+    // 分析一个表达式
+    fn expr(&mut self) -> Result<Expr<'src_lt>, ParseError> {
+        self.subexpr(0)
+    }
+
+    // subexpr -> (simpleexp | unop subexpr) {binop subexpr}
+    // 以优先级爬升的方式分析表达式：只结合优先级高于limit的二元运算符，
+    // 其右操作数按该运算符的结合性确定下限后递归分析，优先级表见precedence模块
+    //
+    // @param limit: 优先级下限
+    fn subexpr(&mut self, limit: u8) -> Result<Expr<'src_lt>, ParseError> {
+        let start = self.token.span.start;
+
+        let mut e = match UnOp::from_token(&self.token.token) {
+            Some(op) => {
+                self.next()?;
+                let operand = self.subexpr(UNARY_PRIORITY)?;
+                Expr::new(
+                    ExprKind::Unary(op, Box::new(operand)),
+                    self.span_from(start),
+                )
+            }
+            None => self.simpleexp()?,
+        };
+
+        while let Some(op) = BinOp::from_token(&self.token.token) {
+            if op.priority() <= limit {
+                break;
+            }
+
+            self.next()?;
+            let rhs = self.subexpr(op.right_priority())?;
+            e = Expr::new(
+                ExprKind::Binary(op, Box::new(e), Box::new(rhs)),
+                self.span_from(start),
+            );
+        }
+
+        Ok(e)
+    }
+
+    // simpleexp -> NUMBER | STRING | NIL | TRUE | FALSE | '...' | constructor |
+    //              FUNCTION body | ifexp | interpstring | suffixedexp
+    fn simpleexp(&mut self) -> Result<Expr<'src_lt>, ParseError> {
+        let start = self.token.span.start;
+
+        let kind = match self.token.token {
+            LexToken::Number(v) => {
+                self.next()?;
+                ExprKind::Number(v)
+            }
+            LexToken::Str(_) | LexToken::ShortStr(_) | LexToken::InterpSimple(_) => {
+                let token = self.take()?;
+                self.string(token)
+            }
+            LexToken::InterpBegin(_) => self.interp()?,
+            LexToken::Nil => {
+                self.next()?;
+                ExprKind::Nil
+            }
+            LexToken::True => {
+                self.next()?;
+                ExprKind::True
+            }
+            LexToken::False => {
+                self.next()?;
+                ExprKind::False
+            }
+            LexToken::Dots => {
+                if !self.func().vararg {
+                    return Err(self.error(ParseErrorKind::VarargOutsideFunction));
+                }
+                self.next()?;
+                ExprKind::Dots
+            }
+            LexToken::CurlyBracketLeft => self.constructor()?,
+            LexToken::Function => {
+                self.next()?;
+                ExprKind::Function(Box::new(self.body(start)?))
+            }
+            LexToken::If if self.stream.status().dialect().has_if_expressions() => {
+                return self.ifexp()
+            }
+            _ => return self.suffixedexp(),
+        };
+
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    // 将字符串Token转化为字符串表达式，不含插值表达式的插值字符串即为普通的字符串
+    //
+    // @param token: 字符串Token
+    //
+    // @return: 字符串表达式
+    fn string(&mut self, token: LexToken<'src_lt>) -> ExprKind<'src_lt> {
+        match token {
+            LexToken::ShortStr(sym) => ExprKind::ShortStr(sym),
+            LexToken::Str(v) => ExprKind::Str(v),
+            LexToken::InterpSimple(v) if v.len() <= MAX_SHORT_LEN => {
+                ExprKind::ShortStr(self.stream.status_mut().interner_mut().intern(&*v))
+            }
+            LexToken::InterpSimple(v) => ExprKind::Str(v),
+            _ => unreachable!(),
+        }
+    }
+
+    // interpstring -> INTERPBEGIN expr {INTERPMID expr} INTERPEND
+    fn interp(&mut self) -> Result<ExprKind<'src_lt>, ParseError> {
+        let mut strings: Vec<Cow<'src_lt, [u8]>> = Vec::new();
+        let mut exprs = Vec::new();
+
+        if let LexToken::InterpBegin(v) = self.take()? {
+            strings.push(v);
+        }
+        loop {
+            exprs.push(self.expr()?);
+            match self.token.token {
+                LexToken::InterpMid(_) => {
+                    if let LexToken::InterpMid(v) = self.take()? {
+                        strings.push(v);
+                    }
+                }
+                LexToken::InterpEnd(_) => {
+                    if let LexToken::InterpEnd(v) = self.take()? {
+                        strings.push(v);
+                    }
+                    break;
+                }
+                _ => return Err(self.error_expected(&["'}'"])),
+            }
+        }
+
+        Ok(ExprKind::Interp(strings, exprs))
+    }
+
+    // ifexp -> IF expr THEN expr {ELSEIF expr THEN expr} ELSE expr
+    fn ifexp(&mut self) -> Result<Expr<'src_lt>, ParseError> {
+        let start = self.token.span.start;
+
+        // 跳过if或elseif
+        self.next()?;
+        let cond = self.expr()?;
+        self.check_next(LexToken::Then)?;
+        let then = self.expr()?;
+        let otherwise = if self.token.token == LexToken::ElseIf {
+            self.ifexp()?
+        } else {
+            self.check_next(LexToken::Else)?;
+            self.expr()?
+        };
+
+        Ok(Expr::new(
+            ExprKind::IfElse(Box::new(cond), Box::new(then), Box::new(otherwise)),
+            self.span_from(start),
+        ))
+    }
+
+    // primaryexp -> NAME | '(' expr ')'
+    fn primaryexp(&mut self) -> Result<Expr<'src_lt>, ParseError> {
+        let start = self.token.span.start;
+
+        let kind = match self.token.token {
+            LexToken::Name(sym) => {
+                self.next()?;
+                ExprKind::Name(sym)
+            }
+            LexToken::RoundBracketLeft => {
+                self.next()?;
+                let e = self.expr()?;
+                self.check_match(
+                    LexToken::RoundBracketRight,
+                    LexToken::RoundBracketLeft,
+                    start.line,
+                )?;
+                ExprKind::Paren(Box::new(e))
+            }
+            _ => return Err(self.error(ParseErrorKind::UnexpectedSymbol)),
+        };
+
+        Ok(Expr::new(kind, self.span_from(start)))
+    }
+
+    // suffixedexp -> primaryexp {'.' NAME | '[' expr ']' | ':' NAME funcargs | funcargs}
+    fn suffixedexp(&mut self) -> Result<Expr<'src_lt>, ParseError> {
+        let start = self.token.span.start;
+        let mut e = self.primaryexp()?;
+
+        loop {
+            let kind = match self.token.token {
+                LexToken::Dot => {
+                    self.next()?;
+                    let name = self.check_name()?;
+                    ExprKind::Field(Box::new(e), name)
+                }
+                LexToken::SquareBracketLeft => {
+                    self.next()?;
+                    let key = self.expr()?;
+                    self.check_next(LexToken::SquareBracketRight)?;
+                    ExprKind::Index(Box::new(e), Box::new(key))
+                }
+                LexToken::MethodCall => {
+                    self.next()?;
+                    let name = self.check_name()?;
+                    let args = self.funcargs()?;
+                    ExprKind::MethodCall(Box::new(e), name, args)
+                }
+                LexToken::RoundBracketLeft
+                | LexToken::Str(_)
+                | LexToken::ShortStr(_)
+                | LexToken::CurlyBracketLeft => {
+                    let args = self.funcargs()?;
+                    ExprKind::Call(Box::new(e), args)
+                }
+                _ => return Ok(e),
+            };
+            e = Expr::new(kind, self.span_from(start));
+        }
+    }
+
+    // funcargs -> '(' [explist] ')' | constructor | STRING
+    fn funcargs(&mut self) -> Result<Vec<Expr<'src_lt>>, ParseError> {
+        let start = self.token.span.start;
+
+        match self.token.token {
+            LexToken::RoundBracketLeft => {
+                self.next()?;
+                let args = if self.token.token == LexToken::RoundBracketRight {
+                    Vec::new()
+                } else {
+                    self.explist()?
+                };
+                self.check_match(
+                    LexToken::RoundBracketRight,
+                    LexToken::RoundBracketLeft,
+                    start.line,
+                )?;
+                Ok(args)
+            }
+            LexToken::CurlyBracketLeft => {
+                let kind = self.constructor()?;
+                Ok(vec![Expr::new(kind, self.span_from(start))])
+            }
+            LexToken::Str(_) | LexToken::ShortStr(_) => {
+                let token = self.take()?;
+                let kind = self.string(token);
+                Ok(vec![Expr::new(kind, self.span_from(start))])
+            }
+            _ => Err(self.error_expected(&["function arguments"])),
+        }
+    }
+
+    // constructor -> '{' [field {sep field} [sep]] '}'
+    fn constructor(&mut self) -> Result<ExprKind<'src_lt>, ParseError> {
+        let line = self.token.span.start.line;
+        self.check_next(LexToken::CurlyBracketLeft)?;
+
+        let mut fields = Vec::new();
+        while self.token.token != LexToken::CurlyBracketRight {
+            fields.push(self.field()?);
+            if !self.test_next(&LexToken::Comma)? && !self.test_next(&LexToken::Semicolon)? {
+                break;
+            }
+        }
+        self.check_match(
+            LexToken::CurlyBracketRight,
+            LexToken::CurlyBracketLeft,
+            line,
+        )?;
+
+        Ok(ExprKind::Table(fields))
+    }
+
+    // field -> NAME '=' expr | '[' expr ']' '=' expr | expr
+    fn field(&mut self) -> Result<Field<'src_lt>, ParseError> {
+        if let LexToken::Name(sym) = self.token.token {
+            if *self.peek()? == LexToken::Assign {
+                self.next()?;
+                self.next()?;
+                return Ok(Field::Named(sym, self.expr()?));
+            }
+        }
+
+        match self.token.token {
+            LexToken::SquareBracketLeft => {
+                self.next()?;
+                let key = self.expr()?;
+                self.check_next(LexToken::SquareBracketRight)?;
+                self.check_next(LexToken::Assign)?;
+                Ok(Field::Index(key, self.expr()?))
+            }
+            _ => Ok(Field::Positional(self.expr()?)),
+        }
+    }
+}
+
+// 将表达式写为前缀形式，便于在测试中比较
+#[cfg(test)]
+pub(crate) fn sexpr(e: &Expr, interner: &Interner) -> String {
+    let name = |sym: Symbol| interner.resolve_str(sym).into_owned();
+    let list = |es: &[Expr]| {
+        es.iter()
+            .map(|e| sexpr(e, interner))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    match &e.kind {
+        ExprKind::Nil => String::from("nil"),
+        ExprKind::True => String::from("true"),
+        ExprKind::False => String::from("false"),
+        ExprKind::Dots => String::from("..."),
+        ExprKind::Number(v) if v.is_int() => v.as_int().to_string(),
+        ExprKind::Number(v) => v.as_float().to_string(),
+        ExprKind::ShortStr(sym) => format!("{:?}", name(*sym)),
+        ExprKind::Str(v) => format!("{:?}", String::from_utf8_lossy(v)),
+        ExprKind::Interp(strings, exprs) => {
+            let strings: Vec<_> = strings
+                .iter()
+                .map(|s| format!("{:?}", String::from_utf8_lossy(s)))
+                .collect();
+            format!("(interp {} {})", strings.join(" "), list(exprs))
+        }
+        ExprKind::Function(f) => format!("(function {})", f.params.len()),
+        ExprKind::Table(fields) => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|f| match f {
+                    Field::Named(k, v) => {
+                        format!("{}={}", name(*k), sexpr(v, interner))
+                    }
+                    Field::Index(k, v) => {
+                        format!("[{}]={}", sexpr(k, interner), sexpr(v, interner))
+                    }
+                    Field::Positional(v) => sexpr(v, interner),
+                })
+                .collect();
+            format!("{{{}}}", fields.join(" "))
+        }
+        ExprKind::Binary(op, l, r) => format!(
+            "({} {} {})",
+            op.text(),
+            sexpr(l, interner),
+            sexpr(r, interner)
+        ),
+        ExprKind::Unary(op, e) => format!("({} {})", op.text(), sexpr(e, interner)),
+        ExprKind::Paren(e) => format!("(paren {})", sexpr(e, interner)),
+        ExprKind::Name(sym) => name(*sym),
+        ExprKind::Field(e, k) => format!("{}.{}", sexpr(e, interner), name(*k)),
+        ExprKind::Index(e, k) => format!("{}[{}]", sexpr(e, interner), sexpr(k, interner)),
+        ExprKind::Call(f, args) => format!("(call {} {})", sexpr(f, interner), list(args)),
+        ExprKind::MethodCall(o, m, args) => format!(
+            "(method {} {} {})",
+            sexpr(o, interner),
+            name(*m),
+            list(args)
+        ),
+        ExprKind::IfElse(c, t, f) => format!(
+            "(if {} {} {})",
+            sexpr(c, interner),
+            sexpr(t, interner),
+            sexpr(f, interner)
+        ),
+    }
+}
+
+// 分析return语句中的单个表达式，并写为前缀形式
+#[cfg(test)]
+pub(crate) fn expr(src: &str, dialect: Dialect) -> String {
+    let src = format!("return {}", src);
+    let mut parser = Parser::with_dialect(&src, dialect);
+    let block = parser.parse_chunk().unwrap();
+    match &block.stats[0].kind {
+        StatKind::Return(exprs) => sexpr(&exprs[0], parser.interner()),
+        _ => panic!("return expected"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(src: &str) -> (Block<'_>, Interner) {
+        parse_dialect(src, Dialect::Lua54)
+    }
+
+    fn parse_dialect(src: &str, dialect: Dialect) -> (Block<'_>, Interner) {
+        let mut parser = Parser::with_dialect(src, dialect);
+        let block = parser.parse_chunk().unwrap();
+        (block, parser.into_interner())
+    }
+
+    fn parse_err(src: &str) -> String {
+        parse_dialect_err(src, Dialect::Lua54)
+    }
+
+    fn parse_dialect_err(src: &str, dialect: Dialect) -> String {
+        let mut parser = Parser::with_dialect(src, dialect);
+        parser.parse_chunk().unwrap_err().to_string()
+    }
+
+    fn parse_recover(src: &str) -> (Block<'_>, Vec<String>) {
+        let mut parser = Parser::new(src);
+        parser.set_recovery(true);
+        let block = parser.parse_chunk().unwrap();
+        let diagnostics = parser.diagnostics().iter().map(|e| e.to_string()).collect();
+        (block, diagnostics)
+    }
+
+    #[test]
+    fn parse_expressions() {
+        let cases = [
+            ("1 + 2 * 3", "(+ 1 (* 2 3))"),
+            ("a.b.c[d]:m(1, 'x')", "(method a.b.c[d] m 1 \"x\")"),
+            (
+                "f 'x' {1, y = 2, [3] = 4; 5}",
+                "(call (call f \"x\") {1 y=2 [3]=4 5})",
+            ),
+            ("(f())", "(paren (call f ))"),
+            ("{...}", "{...}"),
+            ("function(a, b, ...) end", "(function 2)"),
+            ("not nil == false", "(== (not nil) false)"),
+            ("#t + ~x", "(+ (# t) (~ x))"),
+            ("[[long]] .. \"short\"", "(.. \"long\" \"short\")"),
+        ];
+
+        for (src, expect) in cases {
+            assert_eq!(expr(src, Dialect::Lua54), expect, "{}", src);
+        }
+    }
+
+    #[test]
+    fn parse_statements() {
+        let src = "
+            local a <const>, b = 1
+            function t.a.b:m(x) return self end
+            local function f(...) return ... end
+            for i = 1, 10, 2 do break end
+            for k, v in pairs(t) do end
+            while a do a = a - 1 end
+            repeat local c until c
+            if a then elseif b then else end
+            do goto done end
+            ::done::
+            a, t[1] = f()
+            return;
+        ";
+        let (block, interner) = parse(src);
+        let kinds: Vec<_> = block
+            .stats
+            .iter()
+            .map(|s| mem::discriminant(&s.kind))
+            .collect();
+        let expect = [
+            StatKind::Local(Vec::new(), Vec::new()),
+            StatKind::Function(
+                FuncName {
+                    path: Vec::new(),
+                    method: None,
+                },
+                FuncBody {
+                    params: Vec::new(),
+                    vararg: false,
+                    body: Block::default(),
+                    span: Span::default(),
+                },
+            ),
+            StatKind::LocalFunction(
+                interner.get("f").unwrap(),
+                FuncBody {
+                    params: Vec::new(),
+                    vararg: false,
+                    body: Block::default(),
+                    span: Span::default(),
+                },
+            ),
+            StatKind::NumericFor(Box::new(NumericFor {
+                var: interner.get("i").unwrap(),
+                start: Expr::new(ExprKind::Nil, Span::default()),
+                limit: Expr::new(ExprKind::Nil, Span::default()),
+                step: None,
+                body: Block::default(),
+            })),
+            StatKind::GenericFor(Box::new(GenericFor {
+                names: Vec::new(),
+                exprs: Vec::new(),
+                body: Block::default(),
+            })),
+            StatKind::While(Expr::new(ExprKind::Nil, Span::default()), Block::default()),
+            StatKind::Repeat(Block::default(), Expr::new(ExprKind::Nil, Span::default())),
+            StatKind::If(Vec::new(), None),
+            StatKind::Do(Block::default()),
+            StatKind::Label(interner.get("done").unwrap()),
+            StatKind::Assign(Vec::new(), Vec::new()),
+            StatKind::Return(Vec::new()),
+        ];
+        let expect: Vec<_> = expect.iter().map(mem::discriminant).collect();
+        assert_eq!(kinds, expect);
+
+        match &block.stats[0].kind {
+            StatKind::Local(names, exprs) => {
+                assert_eq!(names[0].attrib, Some(Attrib::Const));
+                assert_eq!(names[1].attrib, None);
+                assert_eq!(exprs.len(), 1);
+            }
+            _ => unreachable!(),
+        }
+        match &block.stats[1].kind {
+            StatKind::Function(name, body) => {
+                let path: Vec<_> = name.path.iter().map(|s| interner.resolve_str(*s)).collect();
+                assert_eq!(path, ["t", "a", "b"]);
+                assert_eq!(name.method, interner.get("m"));
+                assert_eq!(body.params.len(), 1);
+                assert_eq!(body.span.start.line, 3);
+            }
+            _ => unreachable!(),
+        }
+        match &block.stats[7].kind {
+            StatKind::If(conds, else_block) => {
+                assert_eq!(conds.len(), 2);
+                assert!(else_block.is_some());
+            }
+            _ => unreachable!(),
+        }
+        match &block.stats[8].kind {
+            StatKind::Do(body) => {
+                assert_eq!(
+                    body.stats[0].kind,
+                    StatKind::Goto(interner.get("done").unwrap())
+                )
+            }
+            _ => unreachable!(),
+        }
+
+        let span = block.stats[5].span;
+        assert_eq!((span.start.line, span.start.column), (7, 13));
+        assert_eq!((span.end.line, span.end.column), (7, 37));
+    }
+
+    #[test]
+    fn parse_luau() {
+        assert_eq!(
+            expr("if a then 1 elseif b then 2 else 3", Dialect::Luau),
+            "(if a 1 (if b 2 3))"
+        );
+        assert_eq!(
+            expr("`a{b}c{d + 1}e`", Dialect::Luau),
+            "(interp \"a\" \"c\" \"e\" b (+ d 1))"
+        );
+        assert_eq!(expr("`abc`", Dialect::Luau), "\"abc\"");
+
+        let (block, interner) = parse_dialect(
+            "x += 1 t[i] ..= 's' while true do continue end continue = 1 continue()",
+            Dialect::Luau,
+        );
+        match &block.stats[0].kind {
+            StatKind::Assign(targets, exprs) => {
+                assert_eq!(sexpr(&targets[0], &interner), "x");
+                assert_eq!(sexpr(&exprs[0], &interner), "(+ x 1)");
+                // 改写得到的x + 1的区间为x到1
+                let (start, end) = (exprs[0].span.start, exprs[0].span.end);
+                assert_eq!((start.offset, end.offset), (0, 6));
+                assert_eq!(start, targets[0].span.start);
+            }
+            _ => panic!("assignment expected"),
+        }
+        match &block.stats[1].kind {
+            StatKind::CompoundAssign(op, target, value) => {
+                assert_eq!(*op, BinOp::Concat);
+                assert_eq!(sexpr(target, &interner), "t[i]");
+                assert_eq!(sexpr(value, &interner), "\"s\"");
+            }
+            _ => panic!("compound assignment expected"),
+        }
+        match &block.stats[2].kind {
+            StatKind::While(_, body) => assert_eq!(body.stats[0].kind, StatKind::Continue),
+            _ => panic!("while expected"),
+        }
+        assert!(matches!(block.stats[3].kind, StatKind::Assign(..)));
+        assert!(matches!(block.stats[4].kind, StatKind::Call(..)));
+
+        // 其他方言中continue只是普通的名字
+        assert_eq!(
+            parse_err("while true do continue end"),
+            "1:24: syntax error near 'end'"
+        );
+
+        // continue与break一样只能用在循环中，循环内定义的函数中也不能使用
+        assert_eq!(
+            parse_dialect_err("x = 1\ncontinue", Dialect::Luau),
+            "2:1: continue outside a loop at line 2 near 'continue'"
+        );
+        assert_eq!(
+            parse_dialect_err(
+                "for i = 1, 2 do f(function() continue end) end",
+                Dialect::Luau
+            ),
+            "1:30: continue outside a loop at line 1 near 'continue'"
+        );
+        let src = "repeat if x then continue end until y for k in t do continue end";
+        assert!(Parser::with_dialect(src, Dialect::Luau)
+            .parse_chunk()
+            .is_ok());
+    }
+
+    #[test]
+    fn parse_shared_interner() {
+        let mut first = Parser::new("local alpha = 'beta'");
+        let block = first.parse_chunk().unwrap();
+        let (alpha, beta) = match &block.stats[0].kind {
+            StatKind::Local(names, exprs) => match exprs[0].kind {
+                ExprKind::ShortStr(sym) => (names[0].name, sym),
+                _ => panic!("short string expected"),
+            },
+            _ => panic!("local expected"),
+        };
+
+        // 第二段源码沿用第一段的驻留表，相同的名字及字符串得到相同的编号
+        let mut second = Parser::with_dialect(
+            "x = 'beta' .. alpha while true do continue end",
+            Dialect::Luau,
+        );
+        second.set_interner(first.into_interner());
+        let block = second.parse_chunk().unwrap();
+        match &block.stats[0].kind {
+            StatKind::Assign(_, exprs) => match &exprs[0].kind {
+                ExprKind::Binary(_, lhs, rhs) => {
+                    assert_eq!(lhs.kind, ExprKind::ShortStr(beta));
+                    assert_eq!(rhs.kind, ExprKind::Name(alpha));
+                }
+                _ => panic!("concatenation expected"),
+            },
+            _ => panic!("assignment expected"),
+        }
+        // 替换驻留表后continue仍被识别为continue语句
+        match &block.stats[1].kind {
+            StatKind::While(_, body) => assert_eq!(body.stats[0].kind, StatKind::Continue),
+            _ => panic!("while expected"),
+        }
+
+        let interner = second.into_interner();
+        assert_eq!(interner.get("alpha"), Some(alpha));
+        assert_eq!(interner.get("beta"), Some(beta));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(parse_err("x = "), "1:5: unexpected symbol near '<eof>'");
+        assert_eq!(parse_err("f() = 1"), "1:5: syntax error near '='");
+        assert_eq!(parse_err("x"), "1:2: syntax error near '<eof>'");
+        assert_eq!(parse_err("if x then"), "1:10: 'end' expected near '<eof>'");
+        assert_eq!(parse_err("for x do"), "1:7: '=' or 'in' expected near 'do'");
+        assert_eq!(parse_err("local 1"), "1:7: <name> expected near '1'");
+        assert_eq!(parse_err("end"), "1:1: <eof> expected near 'end'");
+        assert_eq!(parse_err("return 1 x = 2"), "1:10: <eof> expected near 'x'");
+        assert_eq!(
+            parse_err("return return"),
+            "1:8: unexpected symbol near 'return'"
+        );
+        assert_eq!(
+            parse_err("function f() return ... end"),
+            "1:21: cannot use '...' outside a vararg function near '...'"
+        );
+        assert_eq!(
+            parse_err("local x <foo> = 1"),
+            "1:15: unknown attribute 'foo' near '='"
+        );
+        assert_eq!(
+            parse_err("local x <close>, y <close>"),
+            "1:27: multiple to-be-closed variables in local list near '<eof>'"
+        );
+        assert_eq!(parse_err("x = 'a\n"), "1:7: unfinished string near ''a'");
+        assert_eq!(
+            parse_dialect_err("local x <const> = 1", Dialect::Lua53),
+            "1:9: unexpected symbol near '<'"
+        );
+
+        // 未闭合的结构不在同一行开始时指出其位置
+        assert_eq!(
+            parse_err("x = 1\nlocal function f()\n  return 1\n"),
+            "4:1: 'end' expected (to close 'function' at line 2) near '<eof>'"
+        );
+        assert_eq!(
+            parse_err("while x do\n  f()\nuntil y"),
+            "3:1: 'end' expected (to close 'while' at line 1) near 'until'"
+        );
+        assert_eq!(
+            parse_err("repeat\n  f()\nend"),
+            "3:1: 'until' expected (to close 'repeat' at line 1) near 'end'"
+        );
+        assert_eq!(
+            parse_err("t = {1, 2,\n3 4}"),
+            "2:3: '}' expected (to close '{' at line 1) near '4'"
+        );
+        assert_eq!(parse_err("f(1, 2"), "1:7: ')' expected near '<eof>'");
+
+        // 与lparser.c相同的break、标签及goto检查
+        assert_eq!(
+            parse_err("x = 1\nbreak"),
+            "2:1: break outside a loop at line 2 near 'break'"
+        );
+        assert_eq!(
+            parse_err("while x do f = function() break end end"),
+            "1:27: break outside a loop at line 1 near 'break'"
+        );
+        assert_eq!(
+            parse_err("::a::\n::a::"),
+            "2:3: label 'a' already defined on line 1 near 'a'"
+        );
+        assert_eq!(
+            parse_err("::a:: do ::a:: end"),
+            "1:12: label 'a' already defined on line 1 near 'a'"
+        );
+        assert_eq!(
+            parse_err("goto nowhere"),
+            "1:6: no visible label 'nowhere' for <goto> at line 1 near 'nowhere'"
+        );
+        assert_eq!(
+            parse_err("goto a do ::a:: end"),
+            "1:6: no visible label 'a' for <goto> at line 1 near 'a'"
+        );
+        assert_eq!(
+            parse_err("::a:: function f() goto a end"),
+            "1:25: no visible label 'a' for <goto> at line 1 near 'a'"
+        );
+    }
+
+    #[test]
+    fn parse_goto() {
+        // 向前及向后跳转，跳出内层语句块，以及不同语句块中的同名标签
+        let src = "::top:: while x do if y then goto top end goto next end ::next::
+            for i = 1, 2 do repeat break until z end
+            do ::a:: end do ::a:: goto a end";
+        assert!(Parser::new(src).parse_chunk().is_ok());
+
+        // 恢复模式下记录错误后继续分析
+        let (block, diagnostics) = parse_recover("break\ngoto b\nx = 1");
+        assert_eq!(
+            diagnostics,
+            [
+                "1:1: break outside a loop at line 1 near 'break'",
+                "2:6: no visible label 'b' for <goto> at line 2 near 'b'",
+            ]
+        );
+        assert_eq!(block.stats.len(), 2);
+    }
+
+    #[test]
+    fn parse_recovery() {
+        let src = "local a = 1\nx = = 2\nfunction f()\n  local b = (1 +\nend\nlocal c = $ 1\nprint(a)\nend\ny = 3";
+        let (block, diagnostics) = parse_recover(src);
+        assert_eq!(
+            diagnostics,
+            [
+                "2:5: unexpected symbol near '='",
+                "5:1: unexpected symbol near 'end'",
+                "6:11: unexpected symbol near '$'",
+                "8:1: <eof> expected near 'end'",
+            ]
+        );
+
+        // 出错的语句被丢弃，其余语句保留
+        let kinds: Vec<_> = block
+            .stats
+            .iter()
+            .map(|s| match &s.kind {
+                StatKind::Local(..) => "local",
+                StatKind::Function(..) => "function",
+                StatKind::Call(..) => "call",
+                StatKind::Assign(..) => "assign",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, ["local", "function", "local", "call", "assign"]);
+
+        // 多个未闭合的结构
+        let (block, diagnostics) = parse_recover("if x then\n  while y do\n    f(\n  end\n");
+        assert_eq!(
+            diagnostics,
+            [
+                "4:3: unexpected symbol near 'end'",
+                "5:1: 'end' expected (to close 'if' at line 1) near '<eof>'",
+            ]
+        );
+        assert!(block.stats.is_empty());
+
+        // 未闭合的字符串只报告词法错误，不再报告之后的<eof>处的语法错误
+        let (_, diagnostics) = parse_recover("x = 'abc");
+        assert_eq!(diagnostics, ["1:9: unfinished string near '<eof>'"]);
+
+        // 没有错误时与非恢复模式的结果一致
+        let src = "local t = {1, 2}\nfor i, v in ipairs(t) do print(v) end";
+        let (block, diagnostics) = parse_recover(src);
+        assert!(diagnostics.is_empty());
+        assert_eq!(block, parse(src).0);
+    }
 }
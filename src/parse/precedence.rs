@@ -0,0 +1,172 @@
+use super::ast::{BinOp, UnOp};
+use crate::lex::LexToken;
+
+// 一元运算符的优先级：高于除^以外的所有二元运算符，因此-x^2为-(x^2)，而2^-x的右操作数为-x
+pub const UNARY_PRIORITY: u8 = 12;
+
+// 二元运算符的结合性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    Left,
+    Right,
+}
+
+impl BinOp {
+    // 获取Token对应的二元运算符
+    //
+    // @param token: Token
+    //
+    // @return: 二元运算符
+    pub fn from_token(token: &LexToken<'_>) -> Option<BinOp> {
+        Some(match token {
+            LexToken::Add => BinOp::Add,
+            LexToken::Sub => BinOp::Sub,
+            LexToken::Mul => BinOp::Mul,
+            LexToken::Div => BinOp::Div,
+            LexToken::FloorDiv => BinOp::FloorDiv,
+            LexToken::Mod => BinOp::Mod,
+            LexToken::Pow => BinOp::Pow,
+            LexToken::Concat => BinOp::Concat,
+            LexToken::Equal => BinOp::Equal,
+            LexToken::NotEqual => BinOp::NotEqual,
+            LexToken::Less => BinOp::Less,
+            LexToken::LessEqual => BinOp::LessEqual,
+            LexToken::Greate => BinOp::Greate,
+            LexToken::GreateEqual => BinOp::GreateEqual,
+            LexToken::And => BinOp::And,
+            LexToken::Or => BinOp::Or,
+            LexToken::BitAnd => BinOp::BitAnd,
+            LexToken::BitOr => BinOp::BitOr,
+            LexToken::BitXor => BinOp::BitXor,
+            LexToken::ShiftLeft => BinOp::ShiftLeft,
+            LexToken::ShiftRight => BinOp::ShiftRight,
+            _ => return None,
+        })
+    }
+
+    // 获取优先级，与lparser.c的priority表中的左优先级一致，数值越大结合得越紧。
+    // 比较运算符同级且左结合，a < b < c即(a < b) < c
+    //
+    // @return: 优先级
+    pub fn priority(&self) -> u8 {
+        match self {
+            BinOp::Or => 1,
+            BinOp::And => 2,
+            BinOp::Equal
+            | BinOp::NotEqual
+            | BinOp::Less
+            | BinOp::LessEqual
+            | BinOp::Greate
+            | BinOp::GreateEqual => 3,
+            BinOp::BitOr => 4,
+            BinOp::BitXor => 5,
+            BinOp::BitAnd => 6,
+            BinOp::ShiftLeft | BinOp::ShiftRight => 7,
+            BinOp::Concat => 9,
+            BinOp::Add | BinOp::Sub => 10,
+            BinOp::Mul | BinOp::Div | BinOp::FloorDiv | BinOp::Mod => 11,
+            BinOp::Pow => 14,
+        }
+    }
+
+    // 获取结合性，只有..与^为右结合
+    //
+    // @return: 结合性
+    pub fn assoc(&self) -> Assoc {
+        match self {
+            BinOp::Concat | BinOp::Pow => Assoc::Right,
+            _ => Assoc::Left,
+        }
+    }
+
+    // 获取右操作数的优先级下限，对应lparser.c的priority表中的右优先级。
+    // 右结合的运算符比自身低一级，使右操作数可以继续结合同级的运算符
+    //
+    // @return: 右操作数的优先级下限
+    pub fn right_priority(&self) -> u8 {
+        match self.assoc() {
+            Assoc::Left => self.priority(),
+            Assoc::Right => self.priority() - 1,
+        }
+    }
+}
+
+impl UnOp {
+    // 获取Token对应的一元运算符；~只在支持位运算的方言中被扫描为BitXor
+    //
+    // @param token: Token
+    //
+    // @return: 一元运算符
+    pub fn from_token(token: &LexToken<'_>) -> Option<UnOp> {
+        Some(match token {
+            LexToken::Not => UnOp::Not,
+            LexToken::Sub => UnOp::Neg,
+            LexToken::BitXor => UnOp::BitNot,
+            LexToken::Len => UnOp::Len,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::Dialect;
+    use crate::parse::parser::expr;
+
+    #[test]
+    fn precedence_table() {
+        let cases = [
+            // 一元运算符低于^
+            ("-x^2", "(- (^ x 2))"),
+            ("-x^-y", "(- (^ x (- y)))"),
+            ("2^-3^2", "(^ 2 (- (^ 3 2)))"),
+            ("not a == b", "(== (not a) b)"),
+            ("not not a", "(not (not a))"),
+            ("#t * 2", "(* (# t) 2)"),
+            ("~a & b", "(& (~ a) b)"),
+            ("- -a", "(- (- a))"),
+            // 右结合
+            ("a..b..c", "(.. a (.. b c))"),
+            ("a^b^c", "(^ a (^ b c))"),
+            // 左结合
+            ("a - b - c", "(- (- a b) c)"),
+            ("a // b % c * d", "(* (% (// a b) c) d)"),
+            ("a < b < c", "(< (< a b) c)"),
+            ("a == b ~= c", "(~= (== a b) c)"),
+            ("a < b == c < d", "(< (== (< a b) c) d)"),
+            // 不同优先级
+            ("a or b and c", "(or a (and b c))"),
+            ("a and b or c and d", "(or (and a b) (and c d))"),
+            ("a .. b == c", "(== (.. a b) c)"),
+            ("1 + 2 .. 3 + 4", "(.. (+ 1 2) (+ 3 4))"),
+            ("a .. b + c .. d", "(.. a (.. (+ b c) d))"),
+            ("x << 1 + 2", "(<< x (+ 1 2))"),
+            ("a | b ~ c & d", "(| a (~ b (& c d)))"),
+            ("a << b .. c", "(<< a (.. b c))"),
+            ("a < b | c", "(< a (| b c))"),
+            ("a + b * c ^ d", "(+ a (* b (^ c d)))"),
+            ("(a + b) * c", "(* (paren (+ a b)) c)"),
+        ];
+
+        for (src, expect) in cases {
+            assert_eq!(expr(src, Dialect::Lua54), expect, "{}", src);
+        }
+    }
+
+    #[test]
+    fn precedence_assoc() {
+        assert_eq!(BinOp::Concat.assoc(), Assoc::Right);
+        assert_eq!(BinOp::Pow.assoc(), Assoc::Right);
+        assert_eq!(BinOp::Sub.assoc(), Assoc::Left);
+        assert_eq!(BinOp::Less.assoc(), Assoc::Left);
+        assert_eq!(BinOp::Concat.right_priority(), 8);
+        assert_eq!(BinOp::Pow.right_priority(), 13);
+        assert_eq!(BinOp::Mul.right_priority(), BinOp::Mul.priority());
+
+        assert_eq!(BinOp::from_token(&LexToken::Greate), Some(BinOp::Greate));
+        assert_eq!(BinOp::from_token(&LexToken::Not), None);
+        assert_eq!(UnOp::from_token(&LexToken::Sub), Some(UnOp::Neg));
+        assert_eq!(UnOp::from_token(&LexToken::Add), None);
+    }
+}
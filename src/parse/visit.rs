@@ -0,0 +1,633 @@
+use super::ast::{
+    Block, Expr, ExprKind, Field, FuncBody, FuncName, GenericFor, LocalName, NumericFor, Stat,
+    StatKind,
+};
+use crate::lex::{Span, Symbol};
+
+// 只读遍历AST的访问者。每个visit方法的默认实现调用对应的walk函数访问全部子节点，
+// 重写某个visit方法即可只处理关心的节点；重写后需要继续深入子节点时手动调用walk函数
+pub trait Visitor<'src_lt> {
+    fn visit_block(&mut self, block: &Block<'src_lt>) {
+        walk_block(self, block);
+    }
+
+    fn visit_stat(&mut self, stat: &Stat<'src_lt>) {
+        walk_stat(self, stat);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr<'src_lt>) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_field(&mut self, field: &Field<'src_lt>) {
+        walk_field(self, field);
+    }
+
+    fn visit_func_body(&mut self, body: &FuncBody<'src_lt>) {
+        walk_func_body(self, body);
+    }
+
+    fn visit_numeric_for(&mut self, stat: &NumericFor<'src_lt>) {
+        walk_numeric_for(self, stat);
+    }
+
+    fn visit_generic_for(&mut self, stat: &GenericFor<'src_lt>) {
+        walk_generic_for(self, stat);
+    }
+
+    // 叶子节点，默认不做任何处理
+    fn visit_local_name(&mut self, _name: &LocalName) {}
+
+    // 叶子节点，默认不做任何处理
+    fn visit_func_name(&mut self, _name: &FuncName) {}
+
+    // 表达式中对名字的引用，span为该名字在源码中的区间
+    fn visit_name(&mut self, _name: Symbol, _span: Span) {}
+
+    // 函数的参数，不含方法的self
+    fn visit_param(&mut self, _name: Symbol) {}
+
+    // for循环变量及local function的函数名；local语句声明的变量见visit_local_name
+    fn visit_binding(&mut self, _name: Symbol) {}
+
+    // ::name::定义的标签
+    fn visit_label(&mut self, _name: Symbol) {}
+
+    // goto语句跳转的标签
+    fn visit_goto(&mut self, _name: Symbol) {}
+}
+
+// 可修改AST的访问者，用法与Visitor相同
+pub trait VisitorMut<'src_lt> {
+    fn visit_block_mut(&mut self, block: &mut Block<'src_lt>) {
+        walk_block_mut(self, block);
+    }
+
+    fn visit_stat_mut(&mut self, stat: &mut Stat<'src_lt>) {
+        walk_stat_mut(self, stat);
+    }
+
+    fn visit_expr_mut(&mut self, expr: &mut Expr<'src_lt>) {
+        walk_expr_mut(self, expr);
+    }
+
+    fn visit_field_mut(&mut self, field: &mut Field<'src_lt>) {
+        walk_field_mut(self, field);
+    }
+
+    fn visit_func_body_mut(&mut self, body: &mut FuncBody<'src_lt>) {
+        walk_func_body_mut(self, body);
+    }
+
+    fn visit_numeric_for_mut(&mut self, stat: &mut NumericFor<'src_lt>) {
+        walk_numeric_for_mut(self, stat);
+    }
+
+    fn visit_generic_for_mut(&mut self, stat: &mut GenericFor<'src_lt>) {
+        walk_generic_for_mut(self, stat);
+    }
+
+    // 叶子节点，默认不做任何处理
+    fn visit_local_name_mut(&mut self, _name: &mut LocalName) {}
+
+    // 叶子节点，默认不做任何处理
+    fn visit_func_name_mut(&mut self, _name: &mut FuncName) {}
+
+    // 表达式中对名字的引用，span为该名字在源码中的区间
+    fn visit_name_mut(&mut self, _name: &mut Symbol, _span: Span) {}
+
+    // 函数的参数，不含方法的self
+    fn visit_param_mut(&mut self, _name: &mut Symbol) {}
+
+    // for循环变量及local function的函数名；local语句声明的变量见visit_local_name_mut
+    fn visit_binding_mut(&mut self, _name: &mut Symbol) {}
+
+    // ::name::定义的标签
+    fn visit_label_mut(&mut self, _name: &mut Symbol) {}
+
+    // goto语句跳转的标签
+    fn visit_goto_mut(&mut self, _name: &mut Symbol) {}
+}
+
+// 依次访问语句块中的各条语句
+//
+// @param v: 访问者
+// @param block: 语句块
+pub fn walk_block<'src_lt, V: Visitor<'src_lt> + ?Sized>(v: &mut V, block: &Block<'src_lt>) {
+    for stat in &block.stats {
+        v.visit_stat(stat);
+    }
+}
+
+// 按源码中的顺序访问语句的子节点
+//
+// @param v: 访问者
+// @param stat: 语句
+pub fn walk_stat<'src_lt, V: Visitor<'src_lt> + ?Sized>(v: &mut V, stat: &Stat<'src_lt>) {
+    match &stat.kind {
+        StatKind::Assign(targets, exprs) => {
+            targets.iter().for_each(|e| v.visit_expr(e));
+            exprs.iter().for_each(|e| v.visit_expr(e));
+        }
+        StatKind::CompoundAssign(_, target, value) => {
+            v.visit_expr(target);
+            v.visit_expr(value);
+        }
+        StatKind::Call(e) => v.visit_expr(e),
+        StatKind::Local(names, exprs) => {
+            names.iter().for_each(|n| v.visit_local_name(n));
+            exprs.iter().for_each(|e| v.visit_expr(e));
+        }
+        StatKind::LocalFunction(name, body) => {
+            v.visit_binding(*name);
+            v.visit_func_body(body);
+        }
+        StatKind::Function(name, body) => {
+            v.visit_func_name(name);
+            v.visit_func_body(body);
+        }
+        StatKind::Do(block) => v.visit_block(block),
+        StatKind::While(cond, block) => {
+            v.visit_expr(cond);
+            v.visit_block(block);
+        }
+        StatKind::Repeat(block, cond) => {
+            v.visit_block(block);
+            v.visit_expr(cond);
+        }
+        StatKind::If(conds, else_block) => {
+            for (cond, block) in conds {
+                v.visit_expr(cond);
+                v.visit_block(block);
+            }
+            if let Some(block) = else_block {
+                v.visit_block(block);
+            }
+        }
+        StatKind::NumericFor(stat) => v.visit_numeric_for(stat),
+        StatKind::GenericFor(stat) => v.visit_generic_for(stat),
+        StatKind::Return(exprs) => exprs.iter().for_each(|e| v.visit_expr(e)),
+        StatKind::Goto(name) => v.visit_goto(*name),
+        StatKind::Label(name) => v.visit_label(*name),
+        StatKind::Break | StatKind::Continue => {}
+    }
+}
+
+// 按源码中的顺序访问表达式的子节点
+//
+// @param v: 访问者
+// @param expr: 表达式
+pub fn walk_expr<'src_lt, V: Visitor<'src_lt> + ?Sized>(v: &mut V, expr: &Expr<'src_lt>) {
+    match &expr.kind {
+        ExprKind::Nil
+        | ExprKind::True
+        | ExprKind::False
+        | ExprKind::Dots
+        | ExprKind::Number(_)
+        | ExprKind::ShortStr(_)
+        | ExprKind::Str(_) => {}
+        ExprKind::Name(name) => v.visit_name(*name, expr.span),
+        ExprKind::Interp(_, exprs) => exprs.iter().for_each(|e| v.visit_expr(e)),
+        ExprKind::Function(body) => v.visit_func_body(body),
+        ExprKind::Table(fields) => fields.iter().for_each(|f| v.visit_field(f)),
+        ExprKind::Binary(_, lhs, rhs) => {
+            v.visit_expr(lhs);
+            v.visit_expr(rhs);
+        }
+        ExprKind::Unary(_, e) | ExprKind::Paren(e) | ExprKind::Field(e, _) => v.visit_expr(e),
+        ExprKind::Index(e, key) => {
+            v.visit_expr(e);
+            v.visit_expr(key);
+        }
+        ExprKind::Call(e, args) | ExprKind::MethodCall(e, _, args) => {
+            v.visit_expr(e);
+            args.iter().for_each(|e| v.visit_expr(e));
+        }
+        ExprKind::IfElse(cond, then, otherwise) => {
+            v.visit_expr(cond);
+            v.visit_expr(then);
+            v.visit_expr(otherwise);
+        }
+    }
+}
+
+// 访问表构造器中一项的键与值
+//
+// @param v: 访问者
+// @param field: 表构造器中的一项
+pub fn walk_field<'src_lt, V: Visitor<'src_lt> + ?Sized>(v: &mut V, field: &Field<'src_lt>) {
+    match field {
+        Field::Named(_, value) | Field::Positional(value) => v.visit_expr(value),
+        Field::Index(key, value) => {
+            v.visit_expr(key);
+            v.visit_expr(value);
+        }
+    }
+}
+
+// 访问函数的参数及函数体
+//
+// @param v: 访问者
+// @param body: 函数的参数及函数体
+pub fn walk_func_body<'src_lt, V: Visitor<'src_lt> + ?Sized>(v: &mut V, body: &FuncBody<'src_lt>) {
+    body.params.iter().for_each(|p| v.visit_param(*p));
+    v.visit_block(&body.body);
+}
+
+// 访问数值for循环的循环变量、初值、终值、步长及循环体
+//
+// @param v: 访问者
+// @param stat: 数值for循环
+pub fn walk_numeric_for<'src_lt, V: Visitor<'src_lt> + ?Sized>(
+    v: &mut V,
+    stat: &NumericFor<'src_lt>,
+) {
+    v.visit_binding(stat.var);
+    v.visit_expr(&stat.start);
+    v.visit_expr(&stat.limit);
+    if let Some(step) = &stat.step {
+        v.visit_expr(step);
+    }
+    v.visit_block(&stat.body);
+}
+
+// 访问泛型for循环的循环变量、表达式列表及循环体
+//
+// @param v: 访问者
+// @param stat: 泛型for循环
+pub fn walk_generic_for<'src_lt, V: Visitor<'src_lt> + ?Sized>(
+    v: &mut V,
+    stat: &GenericFor<'src_lt>,
+) {
+    stat.names.iter().for_each(|n| v.visit_binding(*n));
+    stat.exprs.iter().for_each(|e| v.visit_expr(e));
+    v.visit_block(&stat.body);
+}
+
+// 依次访问语句块中的各条语句，可修改
+//
+// @param v: 访问者
+// @param block: 语句块
+pub fn walk_block_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    block: &mut Block<'src_lt>,
+) {
+    for stat in &mut block.stats {
+        v.visit_stat_mut(stat);
+    }
+}
+
+// 按源码中的顺序访问语句的子节点，可修改
+//
+// @param v: 访问者
+// @param stat: 语句
+pub fn walk_stat_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    stat: &mut Stat<'src_lt>,
+) {
+    match &mut stat.kind {
+        StatKind::Assign(targets, exprs) => {
+            targets.iter_mut().for_each(|e| v.visit_expr_mut(e));
+            exprs.iter_mut().for_each(|e| v.visit_expr_mut(e));
+        }
+        StatKind::CompoundAssign(_, target, value) => {
+            v.visit_expr_mut(target);
+            v.visit_expr_mut(value);
+        }
+        StatKind::Call(e) => v.visit_expr_mut(e),
+        StatKind::Local(names, exprs) => {
+            names.iter_mut().for_each(|n| v.visit_local_name_mut(n));
+            exprs.iter_mut().for_each(|e| v.visit_expr_mut(e));
+        }
+        StatKind::LocalFunction(name, body) => {
+            v.visit_binding_mut(name);
+            v.visit_func_body_mut(body);
+        }
+        StatKind::Function(name, body) => {
+            v.visit_func_name_mut(name);
+            v.visit_func_body_mut(body);
+        }
+        StatKind::Do(block) => v.visit_block_mut(block),
+        StatKind::While(cond, block) => {
+            v.visit_expr_mut(cond);
+            v.visit_block_mut(block);
+        }
+        StatKind::Repeat(block, cond) => {
+            v.visit_block_mut(block);
+            v.visit_expr_mut(cond);
+        }
+        StatKind::If(conds, else_block) => {
+            for (cond, block) in conds {
+                v.visit_expr_mut(cond);
+                v.visit_block_mut(block);
+            }
+            if let Some(block) = else_block {
+                v.visit_block_mut(block);
+            }
+        }
+        StatKind::NumericFor(stat) => v.visit_numeric_for_mut(stat),
+        StatKind::GenericFor(stat) => v.visit_generic_for_mut(stat),
+        StatKind::Return(exprs) => exprs.iter_mut().for_each(|e| v.visit_expr_mut(e)),
+        StatKind::Goto(name) => v.visit_goto_mut(name),
+        StatKind::Label(name) => v.visit_label_mut(name),
+        StatKind::Break | StatKind::Continue => {}
+    }
+}
+
+// 按源码中的顺序访问表达式的子节点，可修改
+//
+// @param v: 访问者
+// @param expr: 表达式
+pub fn walk_expr_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    expr: &mut Expr<'src_lt>,
+) {
+    let span = expr.span;
+    match &mut expr.kind {
+        ExprKind::Nil
+        | ExprKind::True
+        | ExprKind::False
+        | ExprKind::Dots
+        | ExprKind::Number(_)
+        | ExprKind::ShortStr(_)
+        | ExprKind::Str(_) => {}
+        ExprKind::Name(name) => v.visit_name_mut(name, span),
+        ExprKind::Interp(_, exprs) => exprs.iter_mut().for_each(|e| v.visit_expr_mut(e)),
+        ExprKind::Function(body) => v.visit_func_body_mut(body),
+        ExprKind::Table(fields) => fields.iter_mut().for_each(|f| v.visit_field_mut(f)),
+        ExprKind::Binary(_, lhs, rhs) => {
+            v.visit_expr_mut(lhs);
+            v.visit_expr_mut(rhs);
+        }
+        ExprKind::Unary(_, e) | ExprKind::Paren(e) | ExprKind::Field(e, _) => v.visit_expr_mut(e),
+        ExprKind::Index(e, key) => {
+            v.visit_expr_mut(e);
+            v.visit_expr_mut(key);
+        }
+        ExprKind::Call(e, args) | ExprKind::MethodCall(e, _, args) => {
+            v.visit_expr_mut(e);
+            args.iter_mut().for_each(|e| v.visit_expr_mut(e));
+        }
+        ExprKind::IfElse(cond, then, otherwise) => {
+            v.visit_expr_mut(cond);
+            v.visit_expr_mut(then);
+            v.visit_expr_mut(otherwise);
+        }
+    }
+}
+
+// 访问表构造器中一项的键与值，可修改
+//
+// @param v: 访问者
+// @param field: 表构造器中的一项
+pub fn walk_field_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    field: &mut Field<'src_lt>,
+) {
+    match field {
+        Field::Named(_, value) | Field::Positional(value) => v.visit_expr_mut(value),
+        Field::Index(key, value) => {
+            v.visit_expr_mut(key);
+            v.visit_expr_mut(value);
+        }
+    }
+}
+
+// 访问函数的参数及函数体，可修改
+//
+// @param v: 访问者
+// @param body: 函数的参数及函数体
+pub fn walk_func_body_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    body: &mut FuncBody<'src_lt>,
+) {
+    body.params.iter_mut().for_each(|p| v.visit_param_mut(p));
+    v.visit_block_mut(&mut body.body);
+}
+
+// 访问数值for循环的循环变量、初值、终值、步长及循环体，可修改
+//
+// @param v: 访问者
+// @param stat: 数值for循环
+pub fn walk_numeric_for_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    stat: &mut NumericFor<'src_lt>,
+) {
+    v.visit_binding_mut(&mut stat.var);
+    v.visit_expr_mut(&mut stat.start);
+    v.visit_expr_mut(&mut stat.limit);
+    if let Some(step) = &mut stat.step {
+        v.visit_expr_mut(step);
+    }
+    v.visit_block_mut(&mut stat.body);
+}
+
+// 访问泛型for循环的循环变量、表达式列表及循环体，可修改
+//
+// @param v: 访问者
+// @param stat: 泛型for循环
+pub fn walk_generic_for_mut<'src_lt, V: VisitorMut<'src_lt> + ?Sized>(
+    v: &mut V,
+    stat: &mut GenericFor<'src_lt>,
+) {
+    stat.names.iter_mut().for_each(|n| v.visit_binding_mut(n));
+    stat.exprs.iter_mut().for_each(|e| v.visit_expr_mut(e));
+    v.visit_block_mut(&mut stat.body);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::{Dialect, LexNumberValue};
+    use crate::parse::{BinOp, Parser};
+
+    // 统计各类节点的数量
+    #[derive(Default)]
+    struct Counter {
+        stats: usize,
+        exprs: usize,
+        calls: usize,
+        locals: usize,
+        bodies: usize,
+    }
+
+    impl<'src_lt> Visitor<'src_lt> for Counter {
+        fn visit_stat(&mut self, stat: &Stat<'src_lt>) {
+            self.stats += 1;
+            walk_stat(self, stat);
+        }
+
+        fn visit_expr(&mut self, expr: &Expr<'src_lt>) {
+            self.exprs += 1;
+            if expr.is_call() {
+                self.calls += 1;
+            }
+            walk_expr(self, expr);
+        }
+
+        fn visit_local_name(&mut self, _name: &LocalName) {
+            self.locals += 1;
+        }
+
+        fn visit_func_body(&mut self, body: &FuncBody<'src_lt>) {
+            self.bodies += 1;
+            walk_func_body(self, body);
+        }
+    }
+
+    // 将整数常量折叠后的加法表达式替换为常量
+    struct FoldAdd;
+
+    impl<'src_lt> VisitorMut<'src_lt> for FoldAdd {
+        fn visit_expr_mut(&mut self, expr: &mut Expr<'src_lt>) {
+            // 先折叠子表达式
+            walk_expr_mut(self, expr);
+
+            if let ExprKind::Binary(BinOp::Add, lhs, rhs) = &expr.kind {
+                if let (ExprKind::Number(a), ExprKind::Number(b)) = (&lhs.kind, &rhs.kind) {
+                    let sum = a.as_int() + b.as_int();
+                    expr.kind = ExprKind::Number(LexNumberValue::Int(sum));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn visit_count() {
+        let src = "
+            local a, b = f(1), {x = g(), [2] = 3, 4}
+            function t.m(...) return ... end
+            for i = 1, #a do print(i) end
+            for k, v in pairs(t) do end
+            repeat local c = function() end until c
+            if a then h() elseif b then else end
+            t[a].b, c = 1 + 2, `x{y}z`
+        ";
+        let mut parser = Parser::with_dialect(src, Dialect::Luau);
+        let block = parser.parse_chunk().unwrap();
+
+        let mut counter = Counter::default();
+        counter.visit_block(&block);
+        assert_eq!(counter.stats, 11);
+        assert_eq!(counter.exprs, 35);
+        assert_eq!(counter.calls, 5);
+        assert_eq!(counter.locals, 3);
+        assert_eq!(counter.bodies, 2);
+    }
+
+    #[test]
+    fn visit_mut_fold() {
+        // 收集全部数字常量
+        struct Numbers(Vec<i64>);
+
+        impl<'src_lt> Visitor<'src_lt> for Numbers {
+            fn visit_expr(&mut self, expr: &Expr<'src_lt>) {
+                if let ExprKind::Number(v) = expr.kind {
+                    self.0.push(v.as_int());
+                }
+                walk_expr(self, expr);
+            }
+        }
+
+        let src = "x = 1 + 2 + 3\nwhile y do z = {(4 + 5), f(6 + a)} end";
+        let mut parser = Parser::new(src);
+        let mut block = parser.parse_chunk().unwrap();
+        FoldAdd.visit_block_mut(&mut block);
+
+        let mut numbers = Numbers(Vec::new());
+        numbers.visit_block(&block);
+        assert_eq!(numbers.0, [6, 9, 6]);
+    }
+
+    #[test]
+    fn visit_names() {
+        // 按种类记录访问到的名字
+        #[derive(Default)]
+        struct Names(Vec<(&'static str, Symbol)>, Vec<Span>);
+
+        impl<'src_lt> Visitor<'src_lt> for Names {
+            fn visit_name(&mut self, name: Symbol, span: Span) {
+                self.0.push(("name", name));
+                self.1.push(span);
+            }
+
+            fn visit_param(&mut self, name: Symbol) {
+                self.0.push(("param", name));
+            }
+
+            fn visit_binding(&mut self, name: Symbol) {
+                self.0.push(("binding", name));
+            }
+
+            fn visit_label(&mut self, name: Symbol) {
+                self.0.push(("label", name));
+            }
+
+            fn visit_goto(&mut self, name: Symbol) {
+                self.0.push(("goto", name));
+            }
+        }
+
+        let src = "
+            local function f(a, b) return a + x end
+            for i = 1, n do goto done end
+            for k, v in pairs(t) do print(k, v.y) end
+            ::done::
+        ";
+        let mut parser = Parser::new(src);
+        let block = parser.parse_chunk().unwrap();
+        let mut names = Names::default();
+        names.visit_block(&block);
+
+        let interner = parser.interner();
+        let resolve = |s: &str| interner.get(s).unwrap();
+        let expect = [
+            ("binding", "f"),
+            ("param", "a"),
+            ("param", "b"),
+            ("name", "a"),
+            ("name", "x"),
+            ("binding", "i"),
+            ("name", "n"),
+            ("goto", "done"),
+            ("binding", "k"),
+            ("binding", "v"),
+            ("name", "pairs"),
+            ("name", "t"),
+            ("name", "print"),
+            ("name", "k"),
+            ("name", "v"),
+            ("label", "done"),
+        ];
+        let expect: Vec<_> = expect.iter().map(|(k, n)| (*k, resolve(n))).collect();
+        assert_eq!(names.0, expect);
+
+        // 名字引用的区间即该名字在源码中的位置
+        let text: Vec<_> = names
+            .1
+            .iter()
+            .map(|s| &src[s.start.offset..s.end.offset])
+            .collect();
+        assert_eq!(text, ["a", "x", "n", "pairs", "t", "print", "k", "v"]);
+
+        // 可修改的访问者重命名全部对x的引用
+        struct Rename(Symbol, Symbol);
+
+        impl<'src_lt> VisitorMut<'src_lt> for Rename {
+            fn visit_name_mut(&mut self, name: &mut Symbol, _span: Span) {
+                if *name == self.0 {
+                    *name = self.1;
+                }
+            }
+        }
+
+        let mut parser = Parser::new("x = x + y");
+        let mut block = parser.parse_chunk().unwrap();
+        let (x, y) = (parser.interner().get("x"), parser.interner().get("y"));
+        Rename(x.unwrap(), y.unwrap()).visit_block_mut(&mut block);
+        let mut names = Names::default();
+        names.visit_block(&block);
+        let y = y.unwrap();
+        assert_eq!(names.0, [("name", y), ("name", y), ("name", y)]);
+    }
+}
@@ -25,6 +25,32 @@ pub const BITS: [u8; 257] = [
     128, 128,
 ];
 
+// 将码点编码为UTF-8字节序列，与Lua 5.4一致，支持至多2^31 - 1的码点
+//
+// @param x: 码点
+//
+// @return: UTF-8字节序列
+pub fn utf8_esc(mut x: u32) -> Vec<u8> {
+    if x < 0x80 {
+        return vec![x as u8];
+    }
+
+    let mut ret = Vec::new();
+    let mut mfb: u32 = 0x3f;
+    loop {
+        ret.push((0x80 | (x & 0x3f)) as u8);
+        x >>= 6;
+        mfb >>= 1;
+        if x <= mfb {
+            break;
+        }
+    }
+    ret.push(((!mfb << 1) | x) as u8);
+    ret.reverse();
+
+    ret
+}
+
 #[macro_export]
 macro_rules! is_alpha {
     ($x: expr, $y: expr) => {
@@ -47,6 +73,11 @@ macro_rules! to_lower {
 }
 
 #[cfg(test)]
+#[allow(
+    unused_imports,
+    clippy::almost_complete_range,
+    clippy::bool_assert_comparison
+)]
 mod tests {
     use crate::toolbox::chr::*;
     use crate::{is_alpha, to_lower, to_upper};
@@ -127,6 +158,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn utf8_esc_ascii() {
+        assert_eq!(utf8_esc(0x41), vec![0x41]);
+        assert_eq!(utf8_esc(0x7f), vec![0x7f]);
+    }
+
+    #[test]
+    fn utf8_esc_multibyte() {
+        for c in ['\u{80}', '\u{7ff}', '\u{4e2d}', '\u{ffff}', '\u{10ffff}'] {
+            let mut buf = [0; 4];
+            assert_eq!(utf8_esc(u32::from(c)), c.encode_utf8(&mut buf).as_bytes());
+        }
+
+        assert_eq!(utf8_esc(0x200000), vec![0xf8, 0x88, 0x80, 0x80, 0x80]);
+        assert_eq!(
+            utf8_esc(0x7fffffff),
+            vec![0xfd, 0xbf, 0xbf, 0xbf, 0xbf, 0xbf]
+        );
+    }
+
     #[test]
     fn lower() {
         for c in 'A'..'Z' {
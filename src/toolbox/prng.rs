@@ -15,7 +15,7 @@ fn tw223_gen(prng: &mut PrngState, idx: usize, r: &mut u64, p: [i32; 3]) {
     if let Some(z) = prng.get_mut(idx) {
         *z = (((*z << p[1]) ^ *z) >> (p[0] - p[2]))
             ^ ((*z & (0xffffffffffffffff << (64 - p[0]))) << p[2]);
-        *r = *r ^ *z;
+        *r ^= *z;
     }
 }
 
@@ -48,6 +48,12 @@ impl PrngState {
     }
 }
 
+impl Default for PrngState {
+    fn default() -> Self {
+        PrngState::new()
+    }
+}
+
 impl Prng for PrngState {
     fn seed_secure(&mut self) {
         self.u[0] = 0xa0d277570a345b8c;